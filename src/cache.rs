@@ -0,0 +1,203 @@
+// The findings of the unchanged files are read from `target/mylint/` instead of linting them again.
+// There's one entry per file, keyed by its path and holding the hash of the source code,
+// so a changed file overwrites its entry instead of adding another one.
+// The whole cache is cleared when the mylint version, the rules or the config change.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::lint::{find_rule, CodePosition, CodeRange};
+use crate::report::{fingerprint, relative_path};
+use crate::{Applicability, Fix, TextEdit, ValidationError};
+
+pub const CACHE_DIR: &str = "target/mylint";
+const KEY_FILE: &str = "key";
+
+pub struct Cache {
+    dir: PathBuf,
+    key: String,
+}
+
+impl Cache {
+    // `config` describes everything else that changes the findings, like the suppressed rules.
+    pub fn open(dir: &Path, config: &str) -> Result<Self, String> {
+        let key = fingerprint(&format!("{}\0{}", env!("CARGO_PKG_VERSION"), config));
+        let key_file = dir.join(KEY_FILE);
+        if fs::read_to_string(&key_file).ok().as_deref() != Some(key.as_str()) {
+            if dir.exists() {
+                fs::remove_dir_all(dir)
+                    .map_err(|err| format!("failed to clear cache {}: {}", dir.display(), err))?;
+            }
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("failed to create cache {}: {}", dir.display(), err))?;
+            fs::write(&key_file, &key)
+                .map_err(|err| format!("failed to write cache {}: {}", dir.display(), err))?;
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            key,
+        })
+    }
+
+    fn entry(&self, path: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.json", fingerprint(relative_path(path))))
+    }
+
+    fn hash(&self, source: &str) -> String {
+        fingerprint(&format!("{}\0{}\0{}", self.key, source.len(), source))
+    }
+
+    // A broken entry or an entry of another version of the file is a miss.
+    pub fn get(&self, path: &str, source: &str) -> Option<Vec<ValidationError>> {
+        let content = fs::read_to_string(self.entry(path)).ok()?;
+        let entry: CachedFile = serde_json::from_str(&content).ok()?;
+        if entry.hash != self.hash(source) {
+            return None;
+        }
+        entry
+            .errors
+            .into_iter()
+            .map(CachedError::into_error)
+            .collect()
+    }
+
+    pub fn put(&self, path: &str, source: &str, errs: &[ValidationError]) {
+        let entry = CachedFile {
+            hash: self.hash(source),
+            errors: errs.iter().map(CachedError::from_error).collect(),
+        };
+        let result = serde_json::to_string(&entry)
+            .map_err(|err| err.to_string())
+            .and_then(|content| {
+                fs::write(self.entry(path), content).map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            debug!("failed to write cache: {}", err);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedFile {
+    // The hash of the source code the errors were found in.
+    hash: String,
+    errors: Vec<CachedError>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedError {
+    code: String,
+    desc: String,
+    // The rule hint is dropped when a concrete fix replaces it.
+    hint: bool,
+    start: (usize, usize, usize),
+    end: (usize, usize, usize),
+    note: Option<String>,
+    fix: Option<CachedFix>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedFix {
+    message: String,
+    machine_applicable: bool,
+    edits: Vec<(usize, usize, String)>,
+}
+
+impl CachedError {
+    fn from_error(err: &ValidationError) -> Self {
+        let position = |p: &CodePosition| (p.row, p.column, p.byte);
+        Self {
+            code: err.rule.code.to_string(),
+            desc: err.rule.desc.to_string(),
+            hint: err.rule.hint.is_some(),
+            start: position(&err.code_range.start),
+            end: position(&err.code_range.end),
+            note: err.note.clone(),
+            fix: err.fix.as_deref().map(|fix| CachedFix {
+                message: fix.message.clone(),
+                machine_applicable: fix.applicability == Applicability::MachineApplicable,
+                edits: fix
+                    .edits
+                    .iter()
+                    .map(|edit| (edit.start, edit.end, edit.replacement.clone()))
+                    .collect(),
+            }),
+        }
+    }
+
+    fn into_error(self) -> Option<ValidationError> {
+        let mut rule = find_rule(&self.code, &self.desc)?;
+        if !self.hint {
+            rule.hint = None;
+        }
+        let position = |(row, column, byte)| CodePosition { row, column, byte };
+        let mut err = ValidationError::new(
+            CodeRange {
+                start: position(self.start),
+                end: position(self.end),
+            },
+            rule,
+        );
+        err.note = self.note;
+        if let Some(fix) = self.fix {
+            let applicability = if fix.machine_applicable {
+                Applicability::MachineApplicable
+            } else {
+                Applicability::Suggestion
+            };
+            let edits = fix
+                .edits
+                .into_iter()
+                .map(|(start, end, replacement)| TextEdit::new(start, end, replacement))
+                .collect();
+            err = err.with_fix(Fix::new(fix.message, applicability, edits));
+        }
+        Some(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AllRulesValidator, SourceCode};
+
+    #[test]
+    fn test_cache() {
+        let dir = std::env::temp_dir().join(format!("mylint-cache-test-{}", std::process::id()));
+        let source_code =
+            "fn f(a: Option<u8>, v: Vec<u8>) -> Option<u8> { Some(a.unwrap() + v[0]) }";
+        let source = assert_some!(SourceCode::parse(source_code));
+        let errs = AllRulesValidator::default().validate_all(&source.get_root_node(), source_code);
+        assert_eq!(errs.len(), 2);
+
+        let cache = assert_ok!(Cache::open(&dir, "config"));
+        assert!(cache.get("src/lib.rs", source_code).is_none());
+        cache.put("src/lib.rs", source_code, &errs);
+        let cached = assert_some!(cache.get("./src/lib.rs", source_code));
+        assert_eq!(cached.len(), errs.len());
+        for (cached, err) in cached.iter().zip(&errs) {
+            assert_eq!(cached.rule.code, err.rule.code);
+            assert_eq!(cached.rule.hint, err.rule.hint);
+            assert_eq!(cached.code_range.start.byte, err.code_range.start.byte);
+            assert_eq!(
+                cached.fix.as_ref().map(|fix| &fix.edits),
+                err.fix.as_ref().map(|fix| &fix.edits)
+            );
+        }
+
+        // A changed file replaces its entry.
+        let changed = "fn f() {}";
+        assert!(cache.get("src/lib.rs", changed).is_none());
+        cache.put("src/lib.rs", changed, &[]);
+        assert_eq!(assert_some!(cache.get("src/lib.rs", changed)).len(), 0);
+        assert!(cache.get("src/lib.rs", source_code).is_none());
+        let entries = assert_ok!(fs::read_dir(&dir)).count();
+        assert_eq!(entries, 2);
+
+        // Another config clears the cache.
+        let cache = assert_ok!(Cache::open(&dir, "other config"));
+        assert!(cache.get("src/lib.rs", changed).is_none());
+        assert_ok!(fs::remove_dir_all(&dir));
+    }
+}
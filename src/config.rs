@@ -0,0 +1,57 @@
+// The `mylint.toml` config file. The command line options take precedence over it.
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+pub const CONFIG_FILE: &str = "mylint.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    // The number of lines to show before and after each finding.
+    pub context: Option<usize>,
+    // The run only fails when the findings exceed these limits.
+    pub max_findings: Option<usize>,
+    pub max_warnings: Option<usize>,
+    pub max_errors: Option<usize>,
+    // The ratchet file of the allowed findings of each rule in each directory.
+    pub ratchet: Option<String>,
+    // The globs of the files to lint and to skip, added to `--include` and `--exclude`.
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    // The globs of the generated files, which are skipped like the ones with the markers.
+    pub generated: Vec<String>,
+}
+
+impl Config {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        toml::from_str(content).map_err(|err| format!("invalid config: {}", err))
+    }
+
+    // Without an explicit path, `mylint.toml` of the current directory is optional.
+    pub fn load(path: Option<&str>) -> Result<Self, String> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(CONFIG_FILE).exists() => CONFIG_FILE,
+            None => return Ok(Self::default()),
+        };
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read config {}: {}", path, err))?;
+        Self::parse(&content).map_err(|err| format!("{}: {}", path, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = assert_ok!(Config::parse("context = 2\nmax-warnings = 10\n"));
+        assert_eq!(config.context, Some(2));
+        assert_eq!(config.max_warnings, Some(10));
+        let config = assert_ok!(Config::parse(""));
+        assert_eq!(config.context, None);
+        assert_err!(Config::parse("contxt = 2\n"));
+    }
+}
@@ -0,0 +1,250 @@
+// Find the files to lint under the given paths.
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+// The `--include` and `--exclude` globs. A glob without `/` also matches the file name.
+#[derive(Debug)]
+pub struct FileSelector {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    generated: GlobSet,
+    generated_globs: Vec<String>,
+}
+
+impl FileSelector {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, String> {
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(glob_set(include)?)
+        };
+        Ok(Self {
+            include,
+            exclude: glob_set(exclude)?,
+            generated: GlobSet::empty(),
+            generated_globs: vec![],
+        })
+    }
+
+    // The globs of the generated files, which are skipped.
+    pub fn with_generated(mut self, globs: &[String]) -> Result<Self, String> {
+        self.generated = glob_set(globs)?;
+        self.generated_globs = globs.to_vec();
+        Ok(self)
+    }
+
+    // Why the file is skipped, or None if it should be linted.
+    pub fn skip_reason(&self, path: &Path) -> Option<String> {
+        if matches(&self.exclude, path) {
+            return Some("excluded by --exclude".to_string());
+        }
+        let relative = path.strip_prefix(".").unwrap_or(path);
+        let matched = self.generated.matches(relative).into_iter().chain(
+            path.file_name()
+                .map(|name| self.generated.matches(name))
+                .unwrap_or_default(),
+        );
+        if let Some(glob) = matched.filter_map(|i| self.generated_globs.get(i)).next() {
+            return Some(format!("generated file matching `{}`", glob));
+        }
+        match &self.include {
+            Some(include) if !matches(include, path) => {
+                Some("not matched by --include".to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+fn glob_set(globs: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = Glob::new(glob.trim_end_matches('/'))
+            .map_err(|err| format!("invalid glob {}: {}", glob, err))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|err| format!("invalid globs: {}", err))
+}
+
+// `./src/lib.rs` should match `src/**`.
+fn matches(set: &GlobSet, path: &Path) -> bool {
+    let relative = path.strip_prefix(".").unwrap_or(path);
+    set.is_match(relative) || path.file_name().is_some_and(|name| set.is_match(name))
+}
+
+// The header lines can only be comments, blank lines and inner attributes.
+const MAX_HEADER_LINES: usize = 30;
+
+// The markers in the header of the generated files.
+// `#![allow(clippy::all)]` is the header of the bindgen output.
+const GENERATED_MARKERS: &[(&str, &str)] = &[
+    ("@generated", "`@generated`"),
+    (
+        "automatically generated by rust-bindgen",
+        "the rust-bindgen header",
+    ),
+    ("#![allow(clippy::all)]", "`#![allow(clippy::all)]`"),
+];
+
+// Why the source code looks generated, or None.
+pub fn generated_marker(source: &str) -> Option<String> {
+    for line in source.lines().take(MAX_HEADER_LINES) {
+        let line = line.trim();
+        let is_header = line.is_empty()
+            || line.starts_with("//")
+            || line.starts_with("/*")
+            || line.starts_with('*')
+            || line.starts_with("#!");
+        if !is_header {
+            break;
+        }
+        for (marker, reason) in GENERATED_MARKERS {
+            if line.contains(marker) {
+                return Some(format!("generated file with {}", reason));
+            }
+        }
+        // `// Code generated by protoc-gen-foo. DO NOT EDIT.`
+        if let Some(rest) = line.find("Code generated ").and_then(|i| line.get(i..)) {
+            if rest.contains("DO NOT EDIT") {
+                return Some("generated file with `Code generated ... DO NOT EDIT`".to_string());
+            }
+        }
+    }
+    None
+}
+
+// The excluded directories are not walked at all.
+// `vendor/**` excludes everything in `vendor`, which is checked with a placeholder file.
+fn excludes_dir(exclude: &GlobSet, dir: &Path) -> bool {
+    matches(exclude, dir) || matches(exclude, &dir.join("__mylint__"))
+}
+
+#[derive(Default)]
+pub struct Files {
+    pub paths: Vec<PathBuf>,
+    // The `.rs` files skipped by the selector with the reason.
+    pub skipped: Vec<(PathBuf, String)>,
+    // The paths that can't be walked, such as the symlink loops,
+    // and the given files which are not Rust files.
+    pub errors: Vec<String>,
+}
+
+// `.gitignore`, `.ignore` and the hidden files are skipped unless `no_ignore` is set.
+// The symlinks are followed and the loops are reported as errors instead of being walked.
+pub fn find_files(roots: &[String], no_ignore: bool, selector: &FileSelector) -> Files {
+    let mut files = Files::default();
+    let mut seen = HashSet::new();
+    let (first, rest) = match roots.split_first() {
+        Some(split) => split,
+        None => return files,
+    };
+    let mut builder = WalkBuilder::new(first);
+    for root in rest {
+        builder.add(root);
+    }
+    builder
+        .standard_filters(!no_ignore)
+        .require_git(false)
+        .follow_links(true)
+        .sort_by_file_path(|a, b| a.cmp(b));
+    let exclude = selector.exclude.clone();
+    let walk = builder
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            entry.depth() == 0 || !is_dir || !excludes_dir(&exclude, entry.path())
+        })
+        .build();
+    for entry in walk {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                files.errors.push(err.to_string());
+                continue;
+            }
+        };
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let given = entry.depth() == 0;
+        let path = entry.into_path();
+        if path.extension() != Some(OsStr::new("rs")) {
+            if given {
+                files
+                    .errors
+                    .push(format!("{}: not a Rust file", path.to_string_lossy()));
+            }
+            continue;
+        }
+        // The same file can be reached from several roots or through the symlinks.
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if !seen.insert(canonical) {
+            continue;
+        }
+        match selector.skip_reason(&path) {
+            Some(reason) => files.skipped.push((path, reason)),
+            None => files.paths.push(path),
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector() {
+        let selector = assert_ok!(FileSelector::new(
+            &["src/**".to_string()],
+            &["*_generated.rs".to_string(), "src/vendor/**".to_string()],
+        ));
+        assert!(selector.skip_reason(Path::new("./src/lib.rs")).is_none());
+        assert!(selector.skip_reason(Path::new("src/lint/mod.rs")).is_none());
+        assert_some!(selector.skip_reason(Path::new("benches/bench.rs")));
+        assert_some!(selector.skip_reason(Path::new("src/proto_generated.rs")));
+        assert_some!(selector.skip_reason(Path::new("src/vendor/lib.rs")));
+        assert!(excludes_dir(&selector.exclude, Path::new("./src/vendor")));
+        assert!(!excludes_dir(&selector.exclude, Path::new("./src/lint")));
+
+        let selector = assert_ok!(FileSelector::new(&[], &[]));
+        assert!(selector.skip_reason(Path::new("build.rs")).is_none());
+        assert_err!(FileSelector::new(&["a[".to_string()], &[]));
+    }
+
+    #[test]
+    fn test_find_files() {
+        let selector = assert_ok!(FileSelector::new(&[], &[]));
+        let roots = ["src/files.rs".to_string(), "Cargo.toml".to_string()];
+        let files = find_files(&roots, false, &selector);
+        assert_eq!(files.paths, vec![PathBuf::from("src/files.rs")]);
+        assert_eq!(
+            files.errors,
+            vec!["Cargo.toml: not a Rust file".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_generated() {
+        let selector = assert_ok!(FileSelector::new(&[], &[]));
+        let selector = assert_ok!(selector.with_generated(&["*.pb.rs".to_string()]));
+        assert_eq!(
+            selector.skip_reason(Path::new("./src/proto/message.pb.rs")),
+            Some("generated file matching `*.pb.rs`".to_string())
+        );
+        assert!(selector.skip_reason(Path::new("src/lib.rs")).is_none());
+
+        assert_some!(generated_marker("// @generated by build.rs\nfn f() {}"));
+        assert_some!(generated_marker(
+            "// Code generated by protoc-gen-rust. DO NOT EDIT.\nfn f() {}"
+        ));
+        assert_some!(generated_marker(
+            "/* automatically generated by rust-bindgen 0.59.1 */\n\n#![allow(clippy::all)]\n"
+        ));
+        assert!(generated_marker("//! The docs.\n\nfn f() {}\n// @generated").is_none());
+    }
+}
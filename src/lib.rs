@@ -1,9 +1,16 @@
 #[macro_use]
 extern crate log;
 
+#[macro_use]
 mod lint;
+pub mod cache;
+pub mod config;
+pub mod files;
+pub mod ratchet;
+pub mod report;
 
+pub use self::lint::fix::{apply_fixes, fix_source, Applicability, Fix, FixedSource, TextEdit};
 pub use self::lint::{
-    default_filter, AllRulesValidator, Filter, NodeIterator, Rule, RuleCode, SourceCode,
-    ValidationError, Validator, RULES,
+    default_filter, unified_diff, AllRulesValidator, ExpectReceivers, Filter, LintConfig,
+    NodeIterator, Rule, RuleCode, Severity, SourceCode, ValidationError, Validator, RULES,
 };
@@ -1,5 +1,9 @@
 use super::filters::get_all_filters;
-use super::rule::Rule;
+use super::fix::Fix;
+use super::grammar::{MACRO_DEFINITION, MACRO_INVOCATION};
+use super::macros::{macro_fragments, macro_rule_fragments};
+use super::rule::{Rule, Severity};
+use super::validators::expect_call::ExpectReceivers;
 use super::validators::get_all_validators;
 use crate::lint::iter::NodeIterator;
 use std::collections::HashSet;
@@ -10,13 +14,15 @@ use tree_sitter::{Language, Node, Parser, Point, Tree};
 pub struct CodePosition {
     pub row: usize,
     pub column: usize,
+    pub byte: usize,
 }
 
 impl CodePosition {
-    pub fn from_point(point: &Point) -> Self {
+    pub fn from_point(point: &Point, byte: usize) -> Self {
         Self {
             row: point.row,
             column: point.column,
+            byte,
         }
     }
 }
@@ -30,8 +36,8 @@ pub struct CodeRange {
 impl CodeRange {
     pub fn from_node(node: &Node) -> Self {
         CodeRange {
-            start: CodePosition::from_point(&node.start_position()),
-            end: CodePosition::from_point(&node.end_position()),
+            start: CodePosition::from_point(&node.start_position(), node.start_byte()),
+            end: CodePosition::from_point(&node.end_position(), node.end_byte()),
         }
     }
 }
@@ -40,17 +46,38 @@ impl CodeRange {
 pub struct ValidationError {
     pub code_range: CodeRange,
     pub rule: Rule,
+    // Extra context like the macro the error comes from.
+    pub note: Option<String>,
+    // Boxed to keep the error small.
+    pub fix: Option<Box<Fix>>,
 }
 
 impl ValidationError {
     pub fn new(code_range: CodeRange, rule: Rule) -> Self {
-        Self { code_range, rule }
+        Self {
+            code_range,
+            rule,
+            note: None,
+            fix: None,
+        }
     }
 
     pub fn from_node(node: &Node, rule: Rule) -> Self {
         let range = CodeRange::from_node(node);
         Self::new(range, rule)
     }
+
+    pub fn with_fix(self, fix: Fix) -> Self {
+        Self {
+            fix: Some(Box::new(fix)),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    pub expect_receivers: ExpectReceivers,
 }
 
 pub trait Validator {
@@ -66,7 +93,7 @@ pub struct AllRulesValidator {
 impl Default for AllRulesValidator {
     fn default() -> Self {
         Self {
-            validators: get_all_validators(),
+            validators: get_all_validators(&LintConfig::default()),
             suppressed_rules: HashSet::new(),
             filter: default_filter(),
         }
@@ -74,32 +101,68 @@ impl Default for AllRulesValidator {
 }
 
 impl AllRulesValidator {
-    pub fn new(suppressed_rules: Vec<String>, filter: Box<dyn Filter>) -> Self {
+    pub fn new(
+        suppressed_rules: Vec<String>,
+        filter: Box<dyn Filter>,
+        config: &LintConfig,
+    ) -> Self {
         Self {
-            validators: get_all_validators(),
+            validators: get_all_validators(config),
             suppressed_rules: HashSet::from_iter(
                 suppressed_rules.into_iter().map(|s| s.to_lowercase()),
             ),
             filter,
         }
     }
-}
 
-impl Validator for AllRulesValidator {
-    fn validate(&self, node: &Node, source: &str) -> Result<(), ValidationError> {
+    // Returns all the errors instead of the first one.
+    pub fn validate_all(&self, node: &Node, source: &str) -> Vec<ValidationError> {
+        let mut errs = vec![];
         for n in NodeIterator::new(node.walk(), source, &(*self.filter)) {
             for validator in &self.validators {
                 if let Err(err) = validator.validate(&n, source) {
-                    if !self
-                        .suppressed_rules
-                        .contains(err.rule.code.to_string().to_lowercase().as_str())
-                    {
-                        return Err(err);
+                    if !self.is_suppressed(&err) {
+                        errs.push(err);
                     }
                 }
             }
+            let fragments = match n.kind() {
+                MACRO_INVOCATION => macro_fragments(&n, source),
+                MACRO_DEFINITION => macro_rule_fragments(&n, source),
+                _ => continue,
+            };
+            for fragment in fragments {
+                let fragment_errs = self.validate_all(&fragment.node(), &fragment.source);
+                errs.extend(fragment_errs.into_iter().map(|err| fragment.map_error(err)));
+            }
+        }
+        // The identifiers in the macro arguments are found both in the token tree
+        // and in the re-parsed fragment.
+        let mut found = HashSet::new();
+        errs.retain(|err| {
+            found.insert((
+                err.code_range.start.byte,
+                err.code_range.end.byte,
+                err.rule.code,
+            ))
+        });
+        errs
+    }
+
+    fn is_suppressed(&self, err: &ValidationError) -> bool {
+        err.rule.severity != Severity::Error
+            && self
+                .suppressed_rules
+                .contains(err.rule.code.to_string().to_lowercase().as_str())
+    }
+}
+
+impl Validator for AllRulesValidator {
+    fn validate(&self, node: &Node, source: &str) -> Result<(), ValidationError> {
+        match self.validate_all(node, source).into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(()),
         }
-        Ok(())
     }
 }
 
@@ -0,0 +1,114 @@
+// Renders the changes made by the fixes as a unified diff.
+const CONTEXT: usize = 3;
+
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+impl<'a> Line<'a> {
+    fn is_change(&self) -> bool {
+        match self {
+            Line::Same(_) => false,
+            Line::Removed(_) | Line::Added(_) => true,
+        }
+    }
+}
+
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let lines: Vec<Line> = diff::slice(&old_lines, &new_lines)
+        .into_iter()
+        .map(|res| match res {
+            diff::Result::Both(line, _) => Line::Same(line),
+            diff::Result::Left(line) => Line::Removed(line),
+            diff::Result::Right(line) => Line::Added(line),
+        })
+        .collect();
+
+    let changes: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.is_change())
+        .map(|(i, _)| i)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Group the changes close to each other into the same hunk.
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for i in changes {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut output = format!("--- a/{}\n+++ b/{}\n", path, path);
+    for (start, end) in hunks {
+        let before = lines.get(..start).unwrap_or(&[]);
+        let hunk = lines.get(start..end).unwrap_or(&[]);
+        let old_start = before
+            .iter()
+            .filter(|l| !matches!(l, Line::Added(_)))
+            .count();
+        let new_start = before
+            .iter()
+            .filter(|l| !matches!(l, Line::Removed(_)))
+            .count();
+        let old_len = hunk.iter().filter(|l| !matches!(l, Line::Added(_))).count();
+        let new_len = hunk
+            .iter()
+            .filter(|l| !matches!(l, Line::Removed(_)))
+            .count();
+        // The line numbers are 1-based, and an empty range points to the line before it.
+        output.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + (old_len > 0) as usize,
+            old_len,
+            new_start + (new_len > 0) as usize,
+            new_len
+        ));
+        for line in hunk {
+            let (prefix, text) = match line {
+                Line::Same(text) => (' ', text),
+                Line::Removed(text) => ('-', text),
+                Line::Added(text) => ('+', text),
+            };
+            output.push(prefix);
+            output.push_str(text);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\n";
+        let expected = "--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+                        @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+                        @@ -7,4 +7,3 @@\n g\n h\n i\n-j\n";
+        assert_eq!(unified_diff("src/lib.rs", old, new), expected);
+    }
+
+    #[test]
+    fn test_merged_hunks() {
+        let old = "a\nb\nc\nd\n";
+        let new = "A\nb\nc\nD\n";
+        let expected = "--- a/f.rs\n+++ b/f.rs\n\
+                        @@ -1,4 +1,4 @@\n-a\n+A\n b\n c\n-d\n+D\n";
+        assert_eq!(unified_diff("f.rs", old, new), expected);
+        assert_eq!(unified_diff("f.rs", old, old), "");
+    }
+}
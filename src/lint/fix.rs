@@ -0,0 +1,188 @@
+// The fixes attached to the validation errors.
+// A fix is a set of text edits on the original source code
+// which are either all applied or none of them.
+use super::core::{AllRulesValidator, SourceCode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applicability {
+    // The fix is safe to apply without looking at it.
+    MachineApplicable,
+    // The fix might change the behavior or not compile. Only shown to the users.
+    Suggestion,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    // The byte range to replace in the source code.
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn new(start: usize, end: usize, replacement: String) -> Self {
+        Self {
+            start,
+            end,
+            replacement,
+        }
+    }
+
+    fn overlaps(&self, other: &TextEdit) -> bool {
+        // Two insertions at the same place are also ambiguous.
+        self.start == other.start || (self.start < other.end && other.start < self.end)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Fix {
+    pub message: String,
+    pub applicability: Applicability,
+    pub edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    pub fn new(message: String, applicability: Applicability, edits: Vec<TextEdit>) -> Self {
+        Self {
+            message,
+            applicability,
+            edits,
+        }
+    }
+}
+
+pub struct FixedSource {
+    pub source: String,
+    // The number of fixes applied.
+    pub applied: usize,
+}
+
+// Applies the fixes which don't overlap with the ones before them.
+// The overlapping fixes are left for the next pass.
+pub fn apply_fixes(source: &str, fixes: &[&Fix]) -> Result<FixedSource, String> {
+    let mut accepted: Vec<&TextEdit> = vec![];
+    let mut applied = 0;
+    for fix in fixes {
+        let valid = fix.edits.iter().enumerate().all(|(i, edit)| {
+            edit.start <= edit.end
+                && source.get(edit.start..edit.end).is_some()
+                && fix.edits.iter().skip(i + 1).all(|e| !edit.overlaps(e))
+                && accepted.iter().all(|e| !edit.overlaps(e))
+        });
+        if valid {
+            accepted.extend(fix.edits.iter());
+            applied += 1;
+        }
+    }
+    accepted.sort_by_key(|edit| edit.start);
+
+    let mut fixed = String::with_capacity(source.len());
+    let mut last = 0;
+    for edit in accepted {
+        fixed.push_str(source.get(last..edit.start).unwrap_or(""));
+        fixed.push_str(&edit.replacement);
+        last = edit.end;
+    }
+    fixed.push_str(source.get(last..).unwrap_or(""));
+
+    // Never make the code unparsable.
+    if parses(&fixed) || !parses(source) {
+        Ok(FixedSource {
+            source: fixed,
+            applied,
+        })
+    } else {
+        Err("the fixed source code can't be parsed".to_string())
+    }
+}
+
+// The fixes could produce new findings or overlap with each other,
+// so the source code is linted and fixed again until nothing changes.
+const MAX_FIX_PASSES: usize = 4;
+
+// Applies all the machine applicable fixes.
+pub fn fix_source(validator: &AllRulesValidator, source: &str) -> Result<FixedSource, String> {
+    let mut fixed = FixedSource {
+        source: source.to_string(),
+        applied: 0,
+    };
+    for _ in 0..MAX_FIX_PASSES {
+        let code = SourceCode::parse(&fixed.source)
+            .ok_or_else(|| "failed to parse source code".to_string())?;
+        let errs = validator.validate_all(&code.get_root_node(), &fixed.source);
+        let fixes: Vec<&Fix> = errs
+            .iter()
+            .filter_map(|err| err.fix.as_deref())
+            .filter(|fix| fix.applicability == Applicability::MachineApplicable)
+            .collect();
+        if fixes.is_empty() {
+            break;
+        }
+        let res = apply_fixes(&fixed.source, &fixes)?;
+        if res.applied == 0 {
+            break;
+        }
+        fixed.source = res.source;
+        fixed.applied += res.applied;
+    }
+    Ok(fixed)
+}
+
+fn parses(source: &str) -> bool {
+    match SourceCode::parse(source) {
+        Some(code) => !code.get_root_node().has_error(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(edits: Vec<(usize, usize, &str)>) -> Fix {
+        let edits = edits
+            .into_iter()
+            .map(|(start, end, s)| TextEdit::new(start, end, s.to_string()))
+            .collect();
+        Fix::new(String::new(), Applicability::MachineApplicable, edits)
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let source = "fn f() { a.unwrap(); b.unwrap(); }";
+        let first = fix(vec![(10, 19, "?")]);
+        let second = fix(vec![(22, 31, "?")]);
+        let fixed = assert_ok!(apply_fixes(source, &[&second, &first]));
+        assert_eq!(fixed.source, "fn f() { a?; b?; }");
+        assert_eq!(fixed.applied, 2);
+    }
+
+    #[test]
+    fn test_overlapping_fixes() {
+        let source = "fn f() { a.unwrap(); }";
+        let first = fix(vec![(10, 19, "?")]);
+        let second = fix(vec![(9, 19, "b"), (20, 20, " c;")]);
+        let fixed = assert_ok!(apply_fixes(source, &[&first, &second]));
+        assert_eq!(fixed.source, "fn f() { a?; }");
+        assert_eq!(fixed.applied, 1);
+    }
+
+    #[test]
+    fn test_fix_source() {
+        let source = "fn f(a: Option<u8>, b: Vec<u8>) -> Option<u8> { Some(a.unwrap() + b[0]) }";
+        let validator = AllRulesValidator::default();
+        let fixed = assert_ok!(fix_source(&validator, source));
+        assert_eq!(
+            fixed.source,
+            "fn f(a: Option<u8>, b: Vec<u8>) -> Option<u8> { Some(a? + b[0]) }"
+        );
+        assert_eq!(fixed.applied, 1);
+    }
+
+    #[test]
+    fn test_unparsable_fix() {
+        let source = "fn f() { a.unwrap(); }";
+        let broken = fix(vec![(10, 19, "(")]);
+        assert!(apply_fixes(source, &[&broken]).is_err());
+    }
+}
@@ -13,3 +13,77 @@ pub const MOD_ITEM: NodeKind = "mod_item";
 pub const ATTRIBUTE_ITEM: NodeKind = "attribute_item";
 pub const META_ITEM: NodeKind = "meta_item";
 pub const ARGUMENTS: NodeKind = "arguments";
+pub const FUNCTION_ITEM: NodeKind = "function_item";
+pub const CLOSURE_EXPRESSION: NodeKind = "closure_expression";
+pub const IMPL_ITEM: NodeKind = "impl_item";
+pub const TYPE_ITEM: NodeKind = "type_item";
+pub const FIELD_DECLARATION: NodeKind = "field_declaration";
+pub const LET_DECLARATION: NodeKind = "let_declaration";
+pub const PARAMETER: NodeKind = "parameter";
+pub const BLOCK: NodeKind = "block";
+pub const CALL_EXPRESSION: NodeKind = "call_expression";
+pub const FIELD_EXPRESSION: NodeKind = "field_expression";
+pub const GENERIC_FUNCTION: NodeKind = "generic_function";
+pub const SCOPED_IDENTIFIER: NodeKind = "scoped_identifier";
+pub const TRY_EXPRESSION: NodeKind = "try_expression";
+pub const STRUCT_EXPRESSION: NodeKind = "struct_expression";
+pub const PARENTHESIZED_EXPRESSION: NodeKind = "parenthesized_expression";
+pub const REFERENCE_EXPRESSION: NodeKind = "reference_expression";
+pub const SELF: NodeKind = "self";
+pub const GENERIC_TYPE: NodeKind = "generic_type";
+pub const TYPE_IDENTIFIER: NodeKind = "type_identifier";
+pub const SCOPED_TYPE_IDENTIFIER: NodeKind = "scoped_type_identifier";
+pub const REFERENCE_TYPE: NodeKind = "reference_type";
+pub const ABSTRACT_TYPE: NodeKind = "abstract_type";
+pub const DYNAMIC_TYPE: NodeKind = "dynamic_type";
+pub const STRING_LITERAL: NodeKind = "string_literal";
+pub const RAW_STRING_LITERAL: NodeKind = "raw_string_literal";
+pub const INTEGER_LITERAL: NodeKind = "integer_literal";
+pub const RANGE_EXPRESSION: NodeKind = "range_expression";
+pub const ARRAY_EXPRESSION: NodeKind = "array_expression";
+pub const ARRAY_TYPE: NodeKind = "array_type";
+pub const IF_EXPRESSION: NodeKind = "if_expression";
+pub const WHILE_EXPRESSION: NodeKind = "while_expression";
+pub const WHILE_LET_EXPRESSION: NodeKind = "while_let_expression";
+pub const FOR_EXPRESSION: NodeKind = "for_expression";
+pub const LOOP_EXPRESSION: NodeKind = "loop_expression";
+pub const IF_LET_EXPRESSION: NodeKind = "if_let_expression";
+pub const MATCH_ARM: NodeKind = "match_arm";
+pub const SHORTHAND_FIELD_IDENTIFIER: NodeKind = "shorthand_field_identifier";
+pub const BINARY_EXPRESSION: NodeKind = "binary_expression";
+pub const ASSIGNMENT_EXPRESSION: NodeKind = "assignment_expression";
+pub const COMPOUND_ASSIGNMENT_EXPR: NodeKind = "compound_assignment_expr";
+pub const UNARY_EXPRESSION: NodeKind = "unary_expression";
+pub const EXPRESSION_STATEMENT: NodeKind = "expression_statement";
+pub const RETURN_EXPRESSION: NodeKind = "return_expression";
+pub const BREAK_EXPRESSION: NodeKind = "break_expression";
+pub const CONTINUE_EXPRESSION: NodeKind = "continue_expression";
+pub const MACRO_INVOCATION: NodeKind = "macro_invocation";
+pub const ASYNC_BLOCK: NodeKind = "async_block";
+pub const TOKEN_TREE: NodeKind = "token_tree";
+pub const MACRO_DEFINITION: NodeKind = "macro_definition";
+pub const MACRO_RULE: NodeKind = "macro_rule";
+pub const METAVARIABLE: NodeKind = "metavariable";
+pub const TOKEN_REPETITION: NodeKind = "token_repetition";
+
+// Field names used by `child_by_field_name`.
+pub type FieldName = &'static str;
+
+pub const NAME: FieldName = "name";
+pub const VALUE: FieldName = "value";
+pub const TYPE: FieldName = "type";
+pub const PATTERN: FieldName = "pattern";
+pub const PATH: FieldName = "path";
+pub const FIELD: FieldName = "field";
+pub const FUNCTION: FieldName = "function";
+pub const PARAMETERS: FieldName = "parameters";
+pub const RETURN_TYPE: FieldName = "return_type";
+pub const TYPE_ARGUMENTS: FieldName = "type_arguments";
+pub const LENGTH: FieldName = "length";
+pub const CONDITION: FieldName = "condition";
+pub const CONSEQUENCE: FieldName = "consequence";
+pub const ALTERNATIVE: FieldName = "alternative";
+pub const BODY: FieldName = "body";
+pub const LEFT: FieldName = "left";
+pub const RIGHT: FieldName = "right";
+pub const MACRO: FieldName = "macro";
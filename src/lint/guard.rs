@@ -0,0 +1,414 @@
+// A basic dominating condition analysis within a function body.
+// It finds the bounds checks which make an index expression `v[i]` safe:
+// - `if i < v.len() { v[i] }` and `while i < v.len() { v[i] }`,
+// - `if !v.is_empty() { v[0] }`,
+// - early return guards like `if i >= v.len() { return; } v[i]`.
+// The check doesn't count when the index or the receiver is assigned or shadowed,
+// or the length is changed by a method like `v.pop()`, between the check and the index.
+// Inside a loop between them, the whole loop body counts since its next iterations come after.
+use tree_sitter::Node;
+
+use crate::lint::grammar::{
+    ALTERNATIVE, ASSIGNMENT_EXPRESSION, BINARY_EXPRESSION, BLOCK, BODY, BREAK_EXPRESSION,
+    CALL_EXPRESSION, CLOSURE_EXPRESSION, COMPOUND_ASSIGNMENT_EXPR, CONDITION, CONSEQUENCE,
+    CONTINUE_EXPRESSION, EXPRESSION_STATEMENT, FIELD, FIELD_EXPRESSION, FOR_EXPRESSION, FUNCTION,
+    FUNCTION_ITEM, IDENTIFIER, IF_EXPRESSION, LEFT, LET_DECLARATION, LOOP_EXPRESSION, MACRO,
+    MACRO_INVOCATION, PARENTHESIZED_EXPRESSION, PATTERN, RETURN_EXPRESSION, RIGHT,
+    UNARY_EXPRESSION, VALUE, WHILE_EXPRESSION, WHILE_LET_EXPRESSION,
+};
+use crate::lint::utils::{find_descendants, node_text};
+
+const DIVERGING_MACROS: [&str; 4] = ["panic", "unreachable", "unimplemented", "todo"];
+
+// Methods which change the length of the receiver.
+const LENGTH_METHODS: [&str; 17] = [
+    "push",
+    "pop",
+    "truncate",
+    "clear",
+    "remove",
+    "swap_remove",
+    "drain",
+    "retain",
+    "retain_mut",
+    "split_off",
+    "dedup",
+    "dedup_by",
+    "dedup_by_key",
+    "resize",
+    "resize_with",
+    "shrink_to",
+    "shrink_to_fit",
+];
+
+const LOOPS: [&str; 4] = [
+    FOR_EXPRESSION,
+    LOOP_EXPRESSION,
+    WHILE_EXPRESSION,
+    WHILE_LET_EXPRESSION,
+];
+
+struct IndexExpr {
+    value: String,
+    index: String,
+    // The variables in the index like `i` in `i + 1` and the receiver like `v` or `self.v`.
+    variables: Vec<String>,
+}
+
+pub fn is_bounds_checked(index_expr: &Node, source: &str) -> bool {
+    let expr = match (index_expr.named_child(0), index_expr.named_child(1)) {
+        (Some(value), Some(index)) => IndexExpr {
+            value: normalize(&value, source),
+            index: normalize(&index, source),
+            variables: find_descendants(&index, IDENTIFIER)
+                .iter()
+                .map(|ident| node_text(ident, source).to_string())
+                .chain(Some(normalize(&value, source)))
+                .collect(),
+        },
+        _ => return false,
+    };
+
+    let mut child = *index_expr;
+    let mut curr = index_expr.parent();
+    while let Some(node) = curr {
+        match node.kind() {
+            IF_EXPRESSION if node.child_by_field_name(CONSEQUENCE) == Some(child) => {
+                if let Some(cond) = node.child_by_field_name(CONDITION) {
+                    if implies(&cond, true, &expr, source)
+                        && !is_mutated(&child, child.start_byte(), index_expr, &expr, source)
+                    {
+                        return true;
+                    }
+                }
+            }
+            WHILE_EXPRESSION if node.child_by_field_name(BODY) == Some(child) => {
+                if let Some(cond) = node.child_by_field_name(CONDITION) {
+                    if implies(&cond, true, &expr, source)
+                        && !is_mutated(&child, child.start_byte(), index_expr, &expr, source)
+                    {
+                        return true;
+                    }
+                }
+            }
+            BLOCK => {
+                for i in 0..node.named_child_count() {
+                    match node.named_child(i) {
+                        Some(stmt) if stmt.end_byte() <= child.start_byte() => {
+                            if is_guard(&stmt, &expr, source)
+                                && !is_mutated(&node, stmt.end_byte(), index_expr, &expr, source)
+                            {
+                                return true;
+                            }
+                        }
+                        _ => break,
+                    }
+                }
+            }
+            FUNCTION_ITEM | CLOSURE_EXPRESSION => return false,
+            _ => (),
+        }
+        child = node;
+        curr = node.parent();
+    }
+    false
+}
+
+// Whether the index or the receiver is assigned or shadowed, or the length of the receiver
+// is changed, in `scope` between the byte `from` and the index expression.
+fn is_mutated(
+    scope: &Node,
+    from: usize,
+    index_expr: &Node,
+    expr: &IndexExpr,
+    source: &str,
+) -> bool {
+    let before = (from, index_expr.start_byte());
+    // The next iterations of the loops between the check and the index run after the whole body.
+    let mut ranges = vec![before];
+    let mut curr = index_expr.parent();
+    while let Some(node) = curr {
+        if node == *scope {
+            break;
+        }
+        if LOOPS.contains(&node.kind()) {
+            if let Some(body) = node.child_by_field_name(BODY) {
+                ranges.push((body.start_byte(), body.end_byte()));
+            }
+        }
+        curr = node.parent();
+    }
+    let within = |node: &Node, (start, end): (usize, usize)| {
+        node.start_byte() >= start && node.end_byte() <= end
+    };
+    let in_ranges = |node: &Node| ranges.iter().any(|&range| within(node, range));
+
+    let assigned = [ASSIGNMENT_EXPRESSION, COMPOUND_ASSIGNMENT_EXPR]
+        .iter()
+        .flat_map(|kind| find_descendants(scope, kind))
+        .filter(in_ranges)
+        .filter_map(|assignment| assignment.child_by_field_name(LEFT))
+        .any(|left| expr.variables.contains(&normalize(&left, source)));
+    // The shadowing only matters before the index, the next iterations don't see it.
+    let shadowed = find_descendants(scope, LET_DECLARATION)
+        .iter()
+        .filter(|stmt| within(stmt, before))
+        .filter_map(|stmt| stmt.child_by_field_name(PATTERN))
+        .flat_map(|pattern| find_descendants(&pattern, IDENTIFIER))
+        .any(|ident| {
+            expr.variables
+                .iter()
+                .any(|var| var == node_text(&ident, source))
+        });
+    let resized = find_descendants(scope, CALL_EXPRESSION)
+        .iter()
+        .filter(|call| in_ranges(call))
+        .any(|call| {
+            LENGTH_METHODS.iter().any(|method| {
+                method_receiver(call, method, source).as_deref() == Some(expr.value.as_str())
+            })
+        });
+    assigned || shadowed || resized
+}
+
+// `if i >= v.len() { return; }`
+fn is_guard(stmt: &Node, expr: &IndexExpr, source: &str) -> bool {
+    let stmt = unwrap_statement(*stmt);
+    if stmt.kind() != IF_EXPRESSION || stmt.child_by_field_name(ALTERNATIVE).is_some() {
+        return false;
+    }
+    let cond = match stmt.child_by_field_name(CONDITION) {
+        Some(cond) => cond,
+        None => return false,
+    };
+    let diverges = match stmt.child_by_field_name(CONSEQUENCE) {
+        Some(block) => block_diverges(&block, source),
+        None => false,
+    };
+    diverges && implies(&cond, false, expr, source)
+}
+
+fn block_diverges(block: &Node, source: &str) -> bool {
+    let count = block.named_child_count();
+    let last = match count.checked_sub(1).and_then(|i| block.named_child(i)) {
+        Some(last) => unwrap_statement(last),
+        None => return false,
+    };
+    match last.kind() {
+        RETURN_EXPRESSION | BREAK_EXPRESSION | CONTINUE_EXPRESSION => true,
+        MACRO_INVOCATION => match last.child_by_field_name(MACRO) {
+            Some(name) => DIVERGING_MACROS.contains(&node_text(&name, source)),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+// Whether `cond` being `holds` makes the index expression safe.
+fn implies(cond: &Node, holds: bool, expr: &IndexExpr, source: &str) -> bool {
+    match cond.kind() {
+        PARENTHESIZED_EXPRESSION => match cond.named_child(0) {
+            Some(inner) => implies(&inner, holds, expr, source),
+            None => false,
+        },
+        UNARY_EXPRESSION if node_text(cond, source).starts_with('!') => match cond.named_child(0) {
+            Some(operand) => implies(&operand, !holds, expr, source),
+            None => false,
+        },
+        BINARY_EXPRESSION => {
+            let (left, right) = match (
+                cond.child_by_field_name(LEFT),
+                cond.child_by_field_name(RIGHT),
+            ) {
+                (Some(left), Some(right)) => (left, right),
+                _ => return false,
+            };
+            let op = operator(cond, source);
+            match (op, holds) {
+                // Any of the conjuncts holds when `a && b` holds.
+                ("&&", true) | ("||", false) => {
+                    implies(&left, holds, expr, source) || implies(&right, holds, expr, source)
+                }
+                _ => {
+                    let op = if holds { Some(op) } else { negate(op) };
+                    match op {
+                        Some("<") => is_index(&left, expr, source) && is_len(&right, expr, source),
+                        Some(">") => is_len(&left, expr, source) && is_index(&right, expr, source),
+                        _ => false,
+                    }
+                }
+            }
+        }
+        // `v.is_empty()` being false makes `v[0]` safe.
+        CALL_EXPRESSION => {
+            !holds
+                && expr.index == "0"
+                && method_receiver(cond, "is_empty", source).as_deref() == Some(expr.value.as_str())
+        }
+        _ => false,
+    }
+}
+
+fn operator<'a>(binary: &Node, source: &'a str) -> &'a str {
+    for i in 0..binary.child_count() {
+        match binary.child(i) {
+            Some(child) if !child.is_named() => return node_text(&child, source),
+            _ => (),
+        }
+    }
+    ""
+}
+
+fn negate(op: &str) -> Option<&'static str> {
+    match op {
+        "<" => Some(">="),
+        ">=" => Some("<"),
+        ">" => Some("<="),
+        "<=" => Some(">"),
+        _ => None,
+    }
+}
+
+fn is_index(node: &Node, expr: &IndexExpr, source: &str) -> bool {
+    normalize(node, source) == expr.index
+}
+
+fn is_len(node: &Node, expr: &IndexExpr, source: &str) -> bool {
+    method_receiver(node, "len", source).as_deref() == Some(expr.value.as_str())
+}
+
+// Returns `v` for `v.method()`.
+fn method_receiver(call: &Node, method: &str, source: &str) -> Option<String> {
+    if call.kind() != CALL_EXPRESSION {
+        return None;
+    }
+    let func = call.child_by_field_name(FUNCTION)?;
+    if func.kind() != FIELD_EXPRESSION
+        || node_text(&func.child_by_field_name(FIELD)?, source) != method
+    {
+        return None;
+    }
+    Some(normalize(&func.child_by_field_name(VALUE)?, source))
+}
+
+fn unwrap_statement(stmt: Node) -> Node {
+    match stmt.kind() {
+        EXPRESSION_STATEMENT => stmt.named_child(0).unwrap_or(stmt),
+        _ => stmt,
+    }
+}
+
+fn normalize(node: &Node, source: &str) -> String {
+    node_text(node, source)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::grammar::INDEX_EXPRESSION;
+    use crate::lint::utils::find_descendants;
+    use crate::SourceCode;
+
+    fn checked(source_code: &str) -> bool {
+        let source = assert_some!(SourceCode::parse(source_code));
+        let root = source.get_root_node();
+        let index_expr = assert_some!(find_descendants(&root, INDEX_EXPRESSION).pop());
+        is_bounds_checked(&index_expr, source_code)
+    }
+
+    #[test]
+    fn test_if_condition() {
+        assert!(checked("fn f() { if i < v.len() { v[i] } }"));
+        assert!(checked("fn f() { if v.len() > i && ok { v[i] } }"));
+        assert!(checked("fn f() { if !v.is_empty() { v[0] } }"));
+        assert!(checked("fn f() { if !(i >= self.v.len()) { self.v[i] } }"));
+        assert!(!checked("fn f() { if i < v.len() { } else { v[i] } }"));
+        assert!(!checked("fn f() { if i < w.len() { v[i] } }"));
+        assert!(!checked("fn f() { if i < v.len() || ok { v[i] } }"));
+        assert!(!checked("fn f() { if !v.is_empty() { v[1] } }"));
+    }
+
+    #[test]
+    fn test_while_condition() {
+        assert!(checked("fn f() { while i < v.len() { v[i]; i += 1; } }"));
+        assert!(!checked("fn f() { while i < v.len() { i += 1; v[i]; } }"));
+    }
+
+    #[test]
+    fn test_mutations() {
+        assert!(!checked("fn f() { if i < v.len() { i = j; v[i] } }"));
+        assert!(!checked(
+            "fn f() { if i + 1 < v.len() { i += 1; v[i + 1] } }"
+        ));
+        assert!(!checked("fn f() { if !v.is_empty() { v.pop(); v[0] } }"));
+        assert!(!checked(
+            "fn f() { if i >= v.len() { return; } v.clear(); v[i] }"
+        ));
+        assert!(checked(
+            "fn f() { if i < v.len() { w.pop(); j += 1; v[i] } }"
+        ));
+    }
+
+    #[test]
+    fn test_length_methods() {
+        assert!(!checked(
+            "fn f() { if i < v.len() { v.swap_remove(0); v[i] } }"
+        ));
+        assert!(!checked(
+            "fn f() { if !v.is_empty() { v.retain(|x| *x > 0); v[0] } }"
+        ));
+        assert!(!checked("fn f() { if i < v.len() { v.dedup(); v[i] } }"));
+    }
+
+    #[test]
+    fn test_reassigned_receiver() {
+        assert!(!checked(
+            "fn f() { if i < v.len() { v = Vec::new(); v[i] } }"
+        ));
+        assert!(!checked(
+            "fn f() { if i < self.v.len() { self.v = vec![]; self.v[i] } }"
+        ));
+    }
+
+    #[test]
+    fn test_shadowed() {
+        assert!(!checked(
+            "fn f() { if i < v.len() { let i = i + 1; v[i] } }"
+        ));
+        assert!(!checked(
+            "fn f() { if i >= v.len() { return; } let v = w; v[i] }"
+        ));
+        assert!(checked("fn f() { if i < v.len() { let j = i + 1; v[i] } }"));
+    }
+
+    #[test]
+    fn test_mutations_later_in_loop() {
+        assert!(!checked(
+            "fn f() { if i < v.len() { loop { v[i]; v.pop(); } } }"
+        ));
+        assert!(!checked(
+            "fn f() { if i < v.len() { for x in xs { v[i]; i += x; } } }"
+        ));
+        // The condition of the while loop is checked again before each iteration.
+        assert!(checked("fn f() { while i < v.len() { v[i]; v.pop(); } }"));
+        assert!(checked(
+            "fn f() { if i < v.len() { for x in xs { v[i]; let i = x; } } }"
+        ));
+    }
+
+    #[test]
+    fn test_early_return() {
+        assert!(checked("fn f() { if i >= v.len() { return; } v[i] }"));
+        assert!(checked(
+            "fn f() { if v.is_empty() || ok { return None; } v[0] }"
+        ));
+        assert!(checked(
+            "fn f() { for i in x { if v.len() <= i { continue; } v[i]; } }"
+        ));
+        assert!(!checked("fn f() { if i >= v.len() { log(); } v[i] }"));
+        assert!(!checked("fn f() { v[i]; if i >= v.len() { return; } }"));
+    }
+}
@@ -4,6 +4,16 @@ pub const UNSAFE_HINT: &str = r#"
 // since they can detect any unsafe usage.
 "#;
 
+pub const INVALID_LITERAL_HINT: &str = r#"
+// Fix the literal so that it can be parsed into the target type.
+
+// Bad
+let port = "70000".parse::<u16>().unwrap();
+
+// Good
+let port = "7000".parse::<u16>().unwrap();
+"#;
+
 pub const FUNCTOR_HINT: &str = r#"
 // For Option or Result:
 
@@ -41,6 +51,10 @@ if arr.len() >= 3 {
 if let Some(v) = arr.get(2) {
     v.do_something();
 }
+"#;
+
+pub const SLICE_EXPR_HINT: &str = r#"
+// Here variable `arr` can be array, Vec, String, and so on.
 
 // Bad
 if "value" == &arr[..5] {
@@ -53,3 +67,20 @@ if "value" == sub_str {
     do_something();
 }
 "#;
+
+pub const MAP_INDEX_HINT: &str = r#"
+// Here variable `map` can be HashMap, BTreeMap, and so on.
+
+// Bad
+if map.contains_key(&key) {
+    map[&key].do_something();
+}
+
+// Good
+if let Some(v) = map.get(&key) {
+    v.do_something();
+}
+
+// Good
+let v = map.get(&key).ok_or_else(|| some_err)?;
+"#;
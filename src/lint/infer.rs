@@ -0,0 +1,726 @@
+// A lightweight and local type inference.
+// It only looks at the current file and classifies an expression as
+// Option, Result, some other known type, or unknown.
+// The sources used are:
+// - let annotations and initializers,
+// - function parameters and return types,
+// - constructor calls like `Some(..)`, `Ok(..)` and `Foo { .. }`,
+// - the usage of `?` on the same variable.
+use tree_sitter::Node;
+
+use crate::lint::grammar::{
+    ABSTRACT_TYPE, BLOCK, BODY, CALL_EXPRESSION, CLOSURE_EXPRESSION, CONSEQUENCE, DYNAMIC_TYPE,
+    FIELD, FIELD_DECLARATION, FIELD_EXPRESSION, FOR_EXPRESSION, FUNCTION, FUNCTION_ITEM,
+    GENERIC_FUNCTION, GENERIC_TYPE, IDENTIFIER, IF_LET_EXPRESSION, IMPL_ITEM, LET_DECLARATION,
+    MATCH_ARM, NAME, PARAMETER, PARAMETERS, PARENTHESIZED_EXPRESSION, PATH, PATTERN,
+    REFERENCE_EXPRESSION, REFERENCE_TYPE, RETURN_TYPE, SCOPED_IDENTIFIER, SCOPED_TYPE_IDENTIFIER,
+    SELF, SHORTHAND_FIELD_IDENTIFIER, STRUCT_EXPRESSION, TRY_EXPRESSION, TYPE, TYPE_ARGUMENTS,
+    TYPE_IDENTIFIER, TYPE_ITEM, VALUE, WHILE_LET_EXPRESSION,
+};
+use crate::lint::utils::{find_ancestor, find_descendants, get_root, node_text};
+
+// Avoid walking through long chains of bindings and aliases.
+const MAX_DEPTH: usize = 8;
+
+// Methods of std types which return an Option.
+const OPTION_METHODS: [&str; 16] = [
+    "ok",
+    "err",
+    "get",
+    "get_mut",
+    "first",
+    "last",
+    "pop",
+    "next",
+    "peek",
+    "find",
+    "position",
+    "checked_add",
+    "checked_sub",
+    "checked_mul",
+    "checked_div",
+    "strip_prefix",
+];
+
+// Methods of std types which return a Result.
+const RESULT_METHODS: [&str; 7] = [
+    "ok_or",
+    "ok_or_else",
+    "parse",
+    "lock",
+    "try_into",
+    "binary_search",
+    "recv",
+];
+
+// Methods of Option and Result which keep the kind of the receiver.
+const ADAPTER_METHODS: [&str; 10] = [
+    "map", "map_err", "and_then", "or", "or_else", "filter", "as_ref", "as_mut", "cloned", "copied",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeClass {
+    Option,
+    Result,
+    // The type is known and it's neither an Option nor a Result.
+    UserType,
+    Unknown,
+}
+
+impl TypeClass {
+    pub fn is_option_or_result(self) -> bool {
+        self == TypeClass::Option || self == TypeClass::Result
+    }
+}
+
+struct Inferred<'a> {
+    class: TypeClass,
+    // The declared type if there's any.
+    ty: Option<Node<'a>>,
+    // The class only comes from the name of a std method like `get`.
+    guessed: bool,
+}
+
+impl<'a> Inferred<'a> {
+    fn unknown() -> Self {
+        Self::from_class(TypeClass::Unknown)
+    }
+
+    fn from_class(class: TypeClass) -> Self {
+        Self {
+            class,
+            ty: None,
+            guessed: false,
+        }
+    }
+
+    fn guessed(class: TypeClass) -> Self {
+        Self {
+            class,
+            ty: None,
+            guessed: true,
+        }
+    }
+
+    fn from_type(ty: Node<'a>, source: &str, depth: usize) -> Self {
+        Self {
+            class: classify_type(&ty, source, depth),
+            ty: Some(ty),
+            guessed: false,
+        }
+    }
+}
+
+pub fn infer_expression(expr: &Node, source: &str) -> TypeClass {
+    infer(expr, source, MAX_DEPTH).class
+}
+
+// Like `infer_expression`, but the classes only guessed from the names of std methods
+// are Unknown since a type of the user could have a method with the same name.
+pub fn infer_declared(expr: &Node, source: &str) -> TypeClass {
+    let inferred = infer(expr, source, MAX_DEPTH);
+    if inferred.guessed {
+        TypeClass::Unknown
+    } else {
+        inferred.class
+    }
+}
+
+// The type explicitly declared for the expression,
+// through a let annotation, a parameter, a field or a return type.
+pub fn declared_type<'a>(expr: &Node<'a>, source: &str) -> Option<Node<'a>> {
+    infer(expr, source, MAX_DEPTH).ty
+}
+
+// The initializer of a variable like `[0; 4]` in `let a = [0; 4];`.
+pub fn initializer<'a>(expr: &Node<'a>, source: &str) -> Option<Node<'a>> {
+    if expr.kind() != IDENTIFIER {
+        return None;
+    }
+    find_binding(expr, node_text(expr, source), source)?.child_by_field_name(VALUE)
+}
+
+// Classify the type returned by the closest function or closure.
+pub fn infer_return_type(node: &Node, source: &str) -> TypeClass {
+    match find_ancestor(node, &[FUNCTION_ITEM, CLOSURE_EXPRESSION]) {
+        Some(func) => match func.child_by_field_name(RETURN_TYPE) {
+            Some(ty) => classify_type(&ty, source, MAX_DEPTH),
+            // Functions without return type return `()`
+            // while the return type of closures could be inferred by the compiler.
+            None if func.kind() == FUNCTION_ITEM => TypeClass::UserType,
+            None => TypeClass::Unknown,
+        },
+        None => TypeClass::Unknown,
+    }
+}
+
+fn infer<'a>(expr: &Node<'a>, source: &str, depth: usize) -> Inferred<'a> {
+    if depth == 0 {
+        return Inferred::unknown();
+    }
+    let depth = depth - 1;
+    match expr.kind() {
+        CALL_EXPRESSION => match expr.child_by_field_name(FUNCTION) {
+            Some(func) => infer_call(&func, source, depth),
+            None => Inferred::unknown(),
+        },
+        TRY_EXPRESSION => match expr.named_child(0) {
+            Some(operand) => unwrap_try(infer(&operand, source, depth), source, depth),
+            None => Inferred::unknown(),
+        },
+        IDENTIFIER => infer_identifier(expr, source, depth),
+        FIELD_EXPRESSION => infer_field(expr, source, depth),
+        SELF | STRUCT_EXPRESSION => Inferred::from_class(TypeClass::UserType),
+        PARENTHESIZED_EXPRESSION => match expr.named_child(0) {
+            Some(inner) => infer(&inner, source, depth),
+            None => Inferred::unknown(),
+        },
+        REFERENCE_EXPRESSION => match expr.child_by_field_name(VALUE) {
+            Some(inner) => infer(&inner, source, depth),
+            None => Inferred::unknown(),
+        },
+        _ => Inferred::unknown(),
+    }
+}
+
+fn infer_call<'a>(func: &Node<'a>, source: &str, depth: usize) -> Inferred<'a> {
+    match func.kind() {
+        IDENTIFIER => match node_text(func, source) {
+            "Some" => Inferred::from_class(TypeClass::Option),
+            "Ok" | "Err" => Inferred::from_class(TypeClass::Result),
+            name => infer_function(func, name, None, source, depth),
+        },
+        SCOPED_IDENTIFIER => {
+            let path = match func.child_by_field_name(PATH) {
+                Some(path) => last_segment(node_text(&path, source)),
+                None => return Inferred::unknown(),
+            };
+            let name = match func.child_by_field_name(NAME) {
+                Some(name) => node_text(&name, source),
+                None => return Inferred::unknown(),
+            };
+            match (path, name) {
+                ("Option", "Some") => Inferred::from_class(TypeClass::Option),
+                ("Result", "Ok") | ("Result", "Err") => Inferred::from_class(TypeClass::Result),
+                _ => infer_function(func, name, Some(path), source, depth),
+            }
+        }
+        FIELD_EXPRESSION => {
+            let receiver = func.child_by_field_name(VALUE);
+            let method = func.child_by_field_name(FIELD);
+            match (receiver, method) {
+                (Some(receiver), Some(method)) => {
+                    infer_method(&receiver, node_text(&method, source), source, depth)
+                }
+                _ => Inferred::unknown(),
+            }
+        }
+        GENERIC_FUNCTION => match func.child_by_field_name(FUNCTION) {
+            Some(inner) => infer_call(&inner, source, depth),
+            None => Inferred::unknown(),
+        },
+        _ => Inferred::unknown(),
+    }
+}
+
+fn infer_method<'a>(receiver: &Node<'a>, method: &str, source: &str, depth: usize) -> Inferred<'a> {
+    // Methods defined in this file take precedence over the std ones,
+    // but only when the receiver is known to be the type of their impl.
+    if let Some(impl_type) = receiver_type_name(receiver, source, depth) {
+        let defined = infer_function(receiver, method, Some(impl_type), source, depth);
+        if defined.class != TypeClass::Unknown {
+            return defined;
+        }
+    }
+
+    if OPTION_METHODS.contains(&method) {
+        Inferred::guessed(TypeClass::Option)
+    } else if RESULT_METHODS.contains(&method) {
+        Inferred::guessed(TypeClass::Result)
+    } else if ADAPTER_METHODS.contains(&method) {
+        let inferred = infer(receiver, source, depth);
+        if inferred.class.is_option_or_result() {
+            Inferred {
+                ty: None,
+                ..inferred
+            }
+        } else {
+            Inferred::unknown()
+        }
+    } else {
+        Inferred::unknown()
+    }
+}
+
+// The name of the type of `self` or of a receiver declared with a type like `p: &Parser`.
+fn receiver_type_name<'a>(receiver: &Node, source: &'a str, depth: usize) -> Option<&'a str> {
+    let name = if receiver.kind() == SELF {
+        "Self"
+    } else {
+        let ty = infer(receiver, source, depth).ty?;
+        let ty = match ty.kind() {
+            REFERENCE_TYPE => ty.child_by_field_name(TYPE)?,
+            _ => ty,
+        };
+        type_name(&ty, source)?
+    };
+    if name != "Self" {
+        return Some(name);
+    }
+    let impl_type = find_ancestor(receiver, &[IMPL_ITEM])?.child_by_field_name(TYPE)?;
+    type_name(&impl_type, source)
+}
+
+// Look up the functions with the specified name in the current file.
+// The result is only known when all the candidates agree.
+fn infer_function<'a>(
+    node: &Node<'a>,
+    name: &str,
+    impl_type: Option<&str>,
+    source: &str,
+    depth: usize,
+) -> Inferred<'a> {
+    let root = get_root(node);
+    let mut result: Option<Inferred> = None;
+    for func in find_descendants(&root, FUNCTION_ITEM) {
+        match func.child_by_field_name(NAME) {
+            Some(n) if node_text(&n, source) == name => (),
+            _ => continue,
+        }
+        if let Some(impl_type) = impl_type {
+            let in_impl = find_ancestor(&func, &[IMPL_ITEM])
+                .and_then(|item| item.child_by_field_name(TYPE))
+                .map(|ty| type_name(&ty, source) == Some(impl_type))
+                .unwrap_or(false);
+            if !in_impl {
+                continue;
+            }
+        }
+        let inferred = match func.child_by_field_name(RETURN_TYPE) {
+            Some(ty) => Inferred::from_type(ty, source, depth),
+            None => Inferred::from_class(TypeClass::UserType),
+        };
+        if !merge(&mut result, inferred, source) {
+            return Inferred::unknown();
+        }
+    }
+    result.unwrap_or_else(Inferred::unknown)
+}
+
+fn infer_identifier<'a>(ident: &Node<'a>, source: &str, depth: usize) -> Inferred<'a> {
+    let name = node_text(ident, source);
+    let inferred = match find_binding(ident, name, source) {
+        Some(binding) => match binding.child_by_field_name(TYPE) {
+            Some(ty) => Inferred::from_type(ty, source, depth),
+            None => match binding.child_by_field_name(VALUE) {
+                Some(value) => infer(&value, source, depth),
+                None => Inferred::unknown(),
+            },
+        },
+        None => Inferred::unknown(),
+    };
+    if inferred.class != TypeClass::Unknown {
+        return inferred;
+    }
+
+    // `name?` only compiles when `name` has the same kind as the return type.
+    let scope = match find_ancestor(ident, &[FUNCTION_ITEM]) {
+        Some(func) => func,
+        None => return inferred,
+    };
+    for try_expr in find_descendants(&scope, TRY_EXPRESSION) {
+        match try_expr.named_child(0) {
+            Some(operand)
+                if operand.kind() == IDENTIFIER && node_text(&operand, source) == name =>
+            {
+                let class = infer_return_type(&try_expr, source);
+                if class.is_option_or_result() {
+                    return Inferred::from_class(class);
+                }
+            }
+            _ => (),
+        }
+    }
+    inferred
+}
+
+// Find the closest let declaration or parameter which binds `name`.
+// The returned node has the `type` and optionally the `value` field.
+// The search stops when the closest binding is a pattern like `Some(name)` or `|name|`,
+// since the type of the pattern is not known.
+fn find_binding<'a>(ident: &Node<'a>, name: &str, source: &str) -> Option<Node<'a>> {
+    let mut child = *ident;
+    let mut curr = ident.parent();
+    while let Some(node) = curr {
+        match node.kind() {
+            BLOCK => {
+                let mut found = None;
+                for i in 0..node.named_child_count() {
+                    let stmt = match node.named_child(i) {
+                        Some(stmt) => stmt,
+                        None => break,
+                    };
+                    if stmt.end_byte() > ident.start_byte() {
+                        break;
+                    }
+                    if stmt.kind() == LET_DECLARATION && pattern_binds(&stmt, name, source) {
+                        found = Some(stmt);
+                    }
+                }
+                if let Some(stmt) = found {
+                    return Some(stmt).filter(|stmt| binds(stmt, name, source));
+                }
+            }
+            FUNCTION_ITEM | CLOSURE_EXPRESSION => {
+                let params = node.child_by_field_name(PARAMETERS)?;
+                for i in 0..params.named_child_count() {
+                    let param = match params.named_child(i) {
+                        Some(param) => param,
+                        None => continue,
+                    };
+                    if param.kind() == PARAMETER && binds(&param, name, source) {
+                        return Some(param);
+                    }
+                    // `|name|`, `|(a, name)|` or `(a, name): (u8, u8)`.
+                    let bound = match param.kind() {
+                        PARAMETER => pattern_binds(&param, name, source),
+                        _ => identifiers(&param, source).contains(&name),
+                    };
+                    if bound {
+                        return None;
+                    }
+                }
+                // Only closures could capture the outer variables.
+                if node.kind() == FUNCTION_ITEM {
+                    return None;
+                }
+            }
+            // `if let Some(name) = .. { name }`, `for name in .. { name }` and `Some(name) => name`.
+            IF_LET_EXPRESSION | WHILE_LET_EXPRESSION | FOR_EXPRESSION | MATCH_ARM => {
+                let scope = match node.kind() {
+                    IF_LET_EXPRESSION => node.child_by_field_name(CONSEQUENCE),
+                    WHILE_LET_EXPRESSION | FOR_EXPRESSION => node.child_by_field_name(BODY),
+                    _ => Some(child),
+                };
+                if scope == Some(child) && pattern_binds(&node, name, source) {
+                    return None;
+                }
+            }
+            _ => (),
+        }
+        child = node;
+        curr = node.parent();
+    }
+    None
+}
+
+// Whether `name` is a plain identifier pattern like in `let name: T`.
+fn binds(node: &Node, name: &str, source: &str) -> bool {
+    match node.child_by_field_name(PATTERN) {
+        Some(pattern) => pattern.kind() == IDENTIFIER && node_text(&pattern, source) == name,
+        None => false,
+    }
+}
+
+// Whether `name` is bound anywhere in the pattern like in `let (a, name) = ..`.
+fn pattern_binds(node: &Node, name: &str, source: &str) -> bool {
+    match node.child_by_field_name(PATTERN) {
+        Some(pattern) => identifiers(&pattern, source).contains(&name),
+        None => false,
+    }
+}
+
+fn identifiers<'a>(pattern: &Node, source: &'a str) -> Vec<&'a str> {
+    let mut idents = find_descendants(pattern, IDENTIFIER);
+    idents.extend(find_descendants(pattern, SHORTHAND_FIELD_IDENTIFIER));
+    idents
+        .iter()
+        .map(|ident| node_text(ident, source))
+        .collect()
+}
+
+// Merge a candidate into the ones which agree on the class so far, and return false if it doesn't.
+// The type is only kept when all the candidates declare the same one.
+fn merge<'a>(result: &mut Option<Inferred<'a>>, inferred: Inferred<'a>, source: &str) -> bool {
+    let prev = match result {
+        Some(prev) => prev,
+        None => {
+            *result = Some(inferred);
+            return true;
+        }
+    };
+    if prev.class != inferred.class {
+        return false;
+    }
+    let same_type = match (prev.ty, inferred.ty) {
+        (Some(a), Some(b)) => normalize_type(&a, source) == normalize_type(&b, source),
+        (None, None) => true,
+        _ => false,
+    };
+    if !same_type {
+        prev.ty = None;
+    }
+    true
+}
+
+fn normalize_type(ty: &Node, source: &str) -> String {
+    node_text(ty, source)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+// Field access like `self.parser`.
+fn infer_field<'a>(expr: &Node<'a>, source: &str, depth: usize) -> Inferred<'a> {
+    let field = match expr.child_by_field_name(FIELD) {
+        Some(field) => node_text(&field, source),
+        None => return Inferred::unknown(),
+    };
+    let root = get_root(expr);
+    let mut result: Option<Inferred> = None;
+    for decl in find_descendants(&root, FIELD_DECLARATION) {
+        match decl.child_by_field_name(NAME) {
+            Some(n) if node_text(&n, source) == field => (),
+            _ => continue,
+        }
+        let inferred = match decl.child_by_field_name(TYPE) {
+            Some(ty) => Inferred::from_type(ty, source, depth),
+            None => continue,
+        };
+        if !merge(&mut result, inferred, source) {
+            return Inferred::unknown();
+        }
+    }
+    result.unwrap_or_else(Inferred::unknown)
+}
+
+// The type of `expr?` is the first type argument of `Option<T>` or `Result<T, E>`.
+fn unwrap_try<'a>(operand: Inferred<'a>, source: &str, depth: usize) -> Inferred<'a> {
+    let ty = match operand.ty {
+        Some(ty) if operand.class.is_option_or_result() => ty,
+        _ => return Inferred::unknown(),
+    };
+    let ty = match ty.kind() {
+        REFERENCE_TYPE => match ty.child_by_field_name(TYPE) {
+            Some(inner) => inner,
+            None => return Inferred::unknown(),
+        },
+        _ => ty,
+    };
+    ty.child_by_field_name(TYPE_ARGUMENTS)
+        .and_then(|args| args.named_child(0))
+        .map(|arg| Inferred::from_type(arg, source, depth))
+        .unwrap_or_else(Inferred::unknown)
+}
+
+fn classify_type(ty: &Node, source: &str, depth: usize) -> TypeClass {
+    if depth == 0 {
+        return TypeClass::Unknown;
+    }
+    match ty.kind() {
+        REFERENCE_TYPE => match ty.child_by_field_name(TYPE) {
+            Some(inner) => classify_type(&inner, source, depth - 1),
+            None => TypeClass::Unknown,
+        },
+        ABSTRACT_TYPE | DYNAMIC_TYPE | "ERROR" => TypeClass::Unknown,
+        GENERIC_TYPE | TYPE_IDENTIFIER | SCOPED_TYPE_IDENTIFIER => match type_name(ty, source) {
+            Some("Option") => TypeClass::Option,
+            Some("Result") => TypeClass::Result,
+            Some(name) => classify_alias(ty, name, source, depth - 1),
+            None => TypeClass::Unknown,
+        },
+        _ => TypeClass::UserType,
+    }
+}
+
+// Resolve the aliases like `type Result<T> = std::result::Result<T, Error>;`.
+fn classify_alias(ty: &Node, name: &str, source: &str, depth: usize) -> TypeClass {
+    let root = get_root(ty);
+    for item in find_descendants(&root, TYPE_ITEM) {
+        match item.child_by_field_name(NAME) {
+            Some(n) if node_text(&n, source) == name => (),
+            _ => continue,
+        }
+        if let Some(aliased) = item.child_by_field_name(TYPE) {
+            return classify_type(&aliased, source, depth);
+        }
+    }
+    TypeClass::UserType
+}
+
+// Get `Result` from `Result<T, E>`, `io::Result<T>` or `Result`.
+pub fn type_name<'a>(ty: &Node, source: &'a str) -> Option<&'a str> {
+    match ty.kind() {
+        GENERIC_TYPE => type_name(&ty.child_by_field_name(TYPE)?, source),
+        TYPE_IDENTIFIER => Some(node_text(ty, source)),
+        SCOPED_TYPE_IDENTIFIER => Some(node_text(&ty.child_by_field_name(NAME)?, source)),
+        _ => None,
+    }
+}
+
+pub fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::grammar::FIELD_IDENTIFIER;
+    use crate::SourceCode;
+
+    // Infer the receiver of the first `expect` call.
+    fn infer_receiver(source_code: &str) -> TypeClass {
+        let source = assert_some!(SourceCode::parse(source_code));
+        let root = source.get_root_node();
+        let method = assert_some!(find_descendants(&root, FIELD_IDENTIFIER)
+            .into_iter()
+            .find(|n| node_text(n, source_code) == "expect"));
+        let receiver = assert_some!(method.parent().and_then(|p| p.child_by_field_name(VALUE)));
+        infer_expression(&receiver, source_code)
+    }
+
+    #[test]
+    fn test_constructor() {
+        let source_code = "fn f() { Some(1).expect(\"\"); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Option);
+        let source_code = "fn f() { let a = Ok(1); a.expect(\"\"); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Result);
+        let source_code = "fn f() { Parser { a: 1 }.expect(Token::A); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::UserType);
+    }
+
+    #[test]
+    fn test_let_annotation() {
+        let source_code = "fn f() { let a: Option<u8> = g(); a.expect(\"\"); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Option);
+        let source_code = "fn f() { let p: Parser = g(); p.expect(Token::A); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::UserType);
+    }
+
+    #[test]
+    fn test_return_type() {
+        let source_code = r#"
+            fn g() -> io::Result<u8> { Ok(1) }
+            fn f() { g().expect(""); }
+        "#;
+        assert_eq!(infer_receiver(source_code), TypeClass::Result);
+        let source_code = r#"
+            impl Parser { fn new() -> Self { Parser {} } }
+            fn f() { Parser::new().expect(Token::A); }
+        "#;
+        assert_eq!(infer_receiver(source_code), TypeClass::UserType);
+    }
+
+    #[test]
+    fn test_parameter_and_field() {
+        let source_code = "fn f(a: &Option<u8>) { a.expect(\"\"); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Option);
+        let source_code = r#"
+            struct S { parser: Parser }
+            impl S { fn f(&self) { self.parser.expect(Token::A); } }
+        "#;
+        assert_eq!(infer_receiver(source_code), TypeClass::UserType);
+        let source_code = "impl Parser { fn f(&self) { self.expect(Token::A); } }";
+        assert_eq!(infer_receiver(source_code), TypeClass::UserType);
+    }
+
+    #[test]
+    fn test_method_defined_in_file() {
+        let source_code = r#"
+            impl Lexer {
+                fn next(&mut self) -> Token { Token::Eof }
+                fn f(&mut self) { self.chars.next().expect("eof"); }
+            }
+        "#;
+        assert_eq!(infer_receiver(source_code), TypeClass::Option);
+        let source_code = r#"
+            impl Parser {
+                fn parse(&mut self) -> Ast { Ast {} }
+                fn f(&mut self, s: &str) { s.parse::<u16>().expect("port"); }
+            }
+        "#;
+        assert_eq!(infer_receiver(source_code), TypeClass::Result);
+        let source_code = r#"
+            impl Lexer {
+                fn next(&mut self) -> Token { Token::Eof }
+                fn f(&mut self, other: &Lexer) { self.next().expect(Token::A); other.next(); }
+            }
+        "#;
+        assert_eq!(infer_receiver(source_code), TypeClass::UserType);
+        let source_code = r#"
+            impl Lexer { fn next(&mut self) -> Token { Token::Eof } }
+            fn f(l: &mut Lexer) { l.next().expect(Token::A); }
+        "#;
+        assert_eq!(infer_receiver(source_code), TypeClass::UserType);
+    }
+
+    #[test]
+    fn test_candidates_with_different_types() {
+        let source_code = r#"
+            struct A { buf: [u8; 4] }
+            struct B { buf: Vec<u8> }
+            fn f(b: B) { b.buf.expect(""); }
+        "#;
+        let source = assert_some!(SourceCode::parse(source_code));
+        let root = source.get_root_node();
+        let field = assert_some!(find_descendants(&root, FIELD_EXPRESSION)
+            .into_iter()
+            .find(|n| node_text(n, source_code) == "b.buf"));
+        assert_eq!(infer_expression(&field, source_code), TypeClass::UserType);
+        assert!(declared_type(&field, source_code).is_none());
+
+        let source_code = r#"
+            struct A { buf: [u8; 4] }
+            struct B { buf: [u8;4] }
+            fn f(b: B) { b.buf.expect(""); }
+        "#;
+        let source = assert_some!(SourceCode::parse(source_code));
+        let root = source.get_root_node();
+        let field = assert_some!(find_descendants(&root, FIELD_EXPRESSION)
+            .into_iter()
+            .find(|n| node_text(n, source_code) == "b.buf"));
+        assert!(declared_type(&field, source_code).is_some());
+    }
+
+    #[test]
+    fn test_shadowed_by_pattern() {
+        let source_code = "fn f(p: Parser) { if let Some(p) = g() { p.expect(\"\"); } }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Unknown);
+        let source_code = "fn f(p: Parser) { while let Some(p) = g() { p.expect(\"\"); } }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Unknown);
+        let source_code = "fn f(p: Parser) { for p in ps { p.expect(\"\"); } }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Unknown);
+        let source_code = "fn f(p: Parser) { match g() { Ok(p) => p.expect(\"\"), _ => () } }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Unknown);
+        let source_code = "fn f() { let p: Parser = g(); h(|p| p.expect(\"\")); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Unknown);
+        let source_code = "fn f(p: Parser) { let (a, p) = g(); p.expect(\"\"); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Unknown);
+
+        // The pattern doesn't bind the name in the value and in the else branch.
+        let source_code =
+            "fn f(p: Parser) { if let Some(p) = g() { } else { p.expect(Token::A); } }";
+        assert_eq!(infer_receiver(source_code), TypeClass::UserType);
+        let source_code = "fn f() { let p: Parser = g(); h(|q: u8| p.expect(Token::A)); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::UserType);
+    }
+
+    #[test]
+    fn test_try_chain() {
+        let source_code = r#"
+            fn g() -> Result<Option<u8>, ()> { Ok(None) }
+            fn f() { g()?.expect(""); }
+        "#;
+        assert_eq!(infer_receiver(source_code), TypeClass::Option);
+        let source_code = "fn f() -> Option<u8> { let a = g(); a?; a.expect(\"\"); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Option);
+    }
+
+    #[test]
+    fn test_unknown() {
+        let source_code = "fn f() { a.expect(\"\"); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Unknown);
+        let source_code = "fn f(a: impl Trait) { a.expect(\"\"); }";
+        assert_eq!(infer_receiver(source_code), TypeClass::Unknown);
+    }
+}
@@ -0,0 +1,193 @@
+// Evaluate `"literal".parse::<T>()` at lint time
+// for the std types whose `FromStr` implementation is known.
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::str::FromStr;
+use tree_sitter::Node;
+
+use crate::lint::grammar::{
+    CALL_EXPRESSION, FIELD, FIELD_EXPRESSION, FUNCTION, GENERIC_FUNCTION, LET_DECLARATION,
+    RAW_STRING_LITERAL, STRING_LITERAL, TYPE, TYPE_ARGUMENTS, VALUE,
+};
+use crate::lint::utils::node_text;
+
+pub enum LiteralParse<'a> {
+    // The literal can be parsed so the unwrap will never panic.
+    Valid,
+    // The literal can't be parsed so the unwrap will always panic.
+    Invalid(Node<'a>),
+    // Not a literal or a type we don't know.
+    Unknown,
+}
+
+// `method` is the `unwrap` or `expect` field identifier.
+pub fn check_literal_parse<'a>(method: &Node<'a>, source: &str) -> LiteralParse<'a> {
+    let unwrap_expr = match method.parent() {
+        Some(expr) if expr.kind() == FIELD_EXPRESSION => expr,
+        _ => return LiteralParse::Unknown,
+    };
+    let parse_call = match unwrap_expr.child_by_field_name(VALUE) {
+        Some(call) if call.kind() == CALL_EXPRESSION => call,
+        _ => return LiteralParse::Unknown,
+    };
+    let (parse_expr, ty) = match parse_call.child_by_field_name(FUNCTION) {
+        // "42".parse::<u16>()
+        Some(func) if func.kind() == GENERIC_FUNCTION => {
+            let ty = func
+                .child_by_field_name(TYPE_ARGUMENTS)
+                .and_then(|args| args.named_child(0));
+            (func.child_by_field_name(FUNCTION), ty)
+        }
+        // let port: u16 = "42".parse().unwrap();
+        Some(func) => (Some(func), annotated_type(&unwrap_expr)),
+        None => return LiteralParse::Unknown,
+    };
+    let (parse_expr, ty) = match (parse_expr, ty) {
+        (Some(parse_expr), Some(ty)) if parse_expr.kind() == FIELD_EXPRESSION => (parse_expr, ty),
+        _ => return LiteralParse::Unknown,
+    };
+    match parse_expr.child_by_field_name(FIELD) {
+        Some(field) if node_text(&field, source) == "parse" => (),
+        _ => return LiteralParse::Unknown,
+    }
+    let literal = match parse_expr.child_by_field_name(VALUE) {
+        Some(literal) => literal,
+        None => return LiteralParse::Unknown,
+    };
+    let value = match literal_value(&literal, source) {
+        Some(value) => value,
+        None => return LiteralParse::Unknown,
+    };
+    let ty = node_text(&ty, source);
+    let ty = ty.rsplit("::").next().unwrap_or(ty);
+    match parses(ty, value) {
+        Some(true) => LiteralParse::Valid,
+        Some(false) => LiteralParse::Invalid(literal),
+        None => LiteralParse::Unknown,
+    }
+}
+
+fn annotated_type<'a>(unwrap_expr: &Node<'a>) -> Option<Node<'a>> {
+    let unwrap_call = unwrap_expr.parent()?;
+    let decl = unwrap_call.parent()?;
+    if decl.kind() != LET_DECLARATION || decl.child_by_field_name(VALUE) != Some(unwrap_call) {
+        return None;
+    }
+    decl.child_by_field_name(TYPE)
+}
+
+// The literals with escape sequences are not supported.
+fn literal_value<'a>(literal: &Node, source: &'a str) -> Option<&'a str> {
+    let text = node_text(literal, source);
+    match literal.kind() {
+        STRING_LITERAL if !text.contains('\\') => text.strip_prefix('"')?.strip_suffix('"'),
+        RAW_STRING_LITERAL => {
+            let text = text.trim_start_matches('r').trim_matches('#');
+            text.strip_prefix('"')?.strip_suffix('"')
+        }
+        _ => None,
+    }
+}
+
+fn parses(ty: &str, s: &str) -> Option<bool> {
+    fn ok<T: FromStr>(s: &str) -> bool {
+        s.parse::<T>().is_ok()
+    }
+
+    let res = match ty {
+        "IpAddr" => ok::<IpAddr>(s),
+        "Ipv4Addr" => ok::<Ipv4Addr>(s),
+        "Ipv6Addr" => ok::<Ipv6Addr>(s),
+        "SocketAddr" => ok::<SocketAddr>(s),
+        "SocketAddrV4" => ok::<SocketAddrV4>(s),
+        "SocketAddrV6" => ok::<SocketAddrV6>(s),
+        "i8" => ok::<i8>(s),
+        "i16" => ok::<i16>(s),
+        "i32" => ok::<i32>(s),
+        "i64" => ok::<i64>(s),
+        "i128" => ok::<i128>(s),
+        "isize" => ok::<isize>(s),
+        "u8" => ok::<u8>(s),
+        "u16" => ok::<u16>(s),
+        "u32" => ok::<u32>(s),
+        "u64" => ok::<u64>(s),
+        "u128" => ok::<u128>(s),
+        "usize" => ok::<usize>(s),
+        "f32" => ok::<f32>(s),
+        "f64" => ok::<f64>(s),
+        "bool" => ok::<bool>(s),
+        "char" => ok::<char>(s),
+        _ => return None,
+    };
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::filters::filter_nothing::NothingFilter;
+    use crate::lint::utils::{assert_source_ok, validate};
+    use crate::lint::validators::expect_call::ExpectCallValidator;
+    use crate::lint::validators::unwrap_call::UnwrapCallValidator;
+    use crate::{AllRulesValidator, LintConfig, RuleCode, SourceCode, Validator};
+
+    #[test]
+    fn test_parses() {
+        assert_eq!(parses("SocketAddr", "127.0.0.1:80"), Some(true));
+        assert_eq!(parses("Ipv4Addr", "::1"), Some(false));
+        assert_eq!(parses("u8", "256"), Some(false));
+        assert_eq!(parses("char", "ab"), Some(false));
+        assert_eq!(parses("Url", "http://localhost"), None);
+    }
+
+    #[test]
+    fn test_valid_literal() {
+        let filter = NothingFilter;
+        let source_code = r#"fn f() { "127.0.0.1:80".parse::<SocketAddr>().unwrap(); }"#;
+        assert_source_ok(source_code, Box::new(UnwrapCallValidator), &filter);
+        let source_code = r#"fn f() { "42".parse::<u16>().expect("valid port"); }"#;
+        assert_source_ok(
+            source_code,
+            Box::new(ExpectCallValidator::default()),
+            &filter,
+        );
+        let source_code = r#"fn f() { let ip: std::net::IpAddr = "::1".parse().unwrap(); }"#;
+        assert_source_ok(source_code, Box::new(UnwrapCallValidator), &filter);
+    }
+
+    #[test]
+    fn test_invalid_literal() {
+        let filter = NothingFilter;
+        let source_code = r#"fn f() { "70000".parse::<u16>().unwrap(); }"#;
+        let res = validate(source_code, Box::new(UnwrapCallValidator), &filter);
+        let err = assert_err!(res);
+        assert_eq!(err.rule.code, RuleCode::InvalidLiteral);
+        assert_eq!(err.code_range.start.column, 9);
+    }
+
+    #[test]
+    fn test_unknown_literal() {
+        let filter = NothingFilter;
+        let source_code = r#"fn f() { "a".parse::<Custom>().unwrap(); }"#;
+        let res = validate(source_code, Box::new(UnwrapCallValidator), &filter);
+        let err = assert_err!(res);
+        assert_eq!(err.rule.code, RuleCode::Unwrap);
+        let source_code = r#"fn f() { s.parse::<u16>().unwrap(); }"#;
+        let res = validate(source_code, Box::new(UnwrapCallValidator), &filter);
+        let err = assert_err!(res);
+        assert_eq!(err.rule.code, RuleCode::Unwrap);
+    }
+
+    #[test]
+    fn test_invalid_literal_not_suppressed() {
+        let source_code = r#"fn f() { "1.2.3".parse::<IpAddr>().unwrap(); }"#;
+        let source = assert_some!(SourceCode::parse(source_code));
+        let validator = AllRulesValidator::new(
+            vec!["unwrap".to_string(), "invalidliteral".to_string()],
+            Box::new(NothingFilter),
+            &LintConfig::default(),
+        );
+        let res = validator.validate(&source.get_root_node(), source_code);
+        let err = assert_err!(res);
+        assert_eq!(err.rule.code, RuleCode::InvalidLiteral);
+    }
+}
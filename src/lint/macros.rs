@@ -0,0 +1,495 @@
+// tree-sitter parses the arguments of macro invocations as token trees instead of expressions.
+// Here the comma separated arguments which look like expressions are re-parsed as Rust code
+// so that the validators can run on them.
+// The arguments are spliced as the statements of a block into a copy of the enclosing function,
+// so that the bindings, the guards and the return type around the invocation are still visible.
+// The same goes for the transcribers of `macro_rules!` definitions,
+// which are parsed on their own.
+use tree_sitter::Node;
+
+use crate::lint::core::{CodePosition, CodeRange, SourceCode, ValidationError};
+use crate::lint::fix::{Fix, TextEdit};
+use crate::lint::grammar::{
+    FUNCTION_ITEM, MACRO_RULE, METAVARIABLE, NAME, RIGHT, TOKEN_REPETITION, TOKEN_TREE,
+};
+use crate::lint::utils::{find_ancestor, find_descendants, node_text};
+
+// The code parsed without a function around it is wrapped in one.
+const PREFIX: &str = "fn __mylint_macro() {\n";
+const SUFFIX: &str = "\n;}";
+
+// A piece of code inside a macro which is re-parsed on its own.
+pub struct Fragment {
+    pub source: String,
+    pub code: SourceCode,
+    // Where the fragment starts in the original source.
+    start: CodePosition,
+    // Where the fragment starts in `source`.
+    offset: CodePosition,
+    // The byte range of the block spliced into the enclosing function.
+    spliced: Option<(usize, usize)>,
+    // Attached to the errors found in the fragment.
+    note: Option<String>,
+    // The metavariables are replaced so the fixes can't be applied to the original source.
+    placeholders: bool,
+}
+
+impl Fragment {
+    fn parse(text: &str, start: CodePosition) -> Option<Self> {
+        let source = format!("{}{}{}", PREFIX, text, SUFFIX);
+        let offset = position_of(&source, PREFIX.len());
+        Self::new(source, start, offset, None)
+    }
+
+    // `println!("{}", v[i]);` in `fn f(v: &[u8], i: usize) { .. }` becomes
+    // `fn f(v: &[u8], i: usize) { ..         {"{}"; v[i]}; .. }`.
+    // Only the kept ranges and the separators of the invocation are left,
+    // so the whole function is parsed once and the positions don't move.
+    fn splice(
+        invocation: &Node,
+        tree: &Node,
+        kept: &[(usize, usize)],
+        separators: &[usize],
+        source: &str,
+    ) -> Option<Self> {
+        let func = find_ancestor(invocation, &[FUNCTION_ITEM])?;
+        let base = func.start_byte();
+        let mut bytes = source.get(base..func.end_byte())?.as_bytes().to_vec();
+        for i in invocation.start_byte()..invocation.end_byte() {
+            let byte = bytes.get_mut(i - base)?;
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+        for &(start, end) in kept {
+            bytes
+                .get_mut(start - base..end - base)?
+                .copy_from_slice(source.as_bytes().get(start..end)?);
+        }
+        for &separator in separators {
+            *bytes.get_mut(separator - base)? = b';';
+        }
+        let block = (tree.start_byte() - base, tree.end_byte() - base);
+        *bytes.get_mut(block.0)? = b'{';
+        *bytes.get_mut(block.1.checked_sub(1)?)? = b'}';
+        let spliced = String::from_utf8(bytes).ok()?;
+        let origin = position_of(&spliced, 0);
+        Self::new(spliced, position_of(source, base), origin, Some(block))
+    }
+
+    fn new(
+        source: String,
+        start: CodePosition,
+        offset: CodePosition,
+        spliced: Option<(usize, usize)>,
+    ) -> Option<Self> {
+        let code = SourceCode::parse(&source)?;
+        if code.get_root_node().has_error() {
+            return None;
+        }
+        Some(Self {
+            source,
+            code,
+            start,
+            offset,
+            spliced,
+            note: None,
+            placeholders: false,
+        })
+    }
+
+    // The node to validate: the spliced block or the whole wrapper.
+    pub fn node(&self) -> Node<'_> {
+        let root = self.code.get_root_node();
+        match self.spliced {
+            Some((start, end)) => root.descendant_for_byte_range(start, end).unwrap_or(root),
+            None => root,
+        }
+    }
+
+    pub fn map_error(&self, err: ValidationError) -> ValidationError {
+        let code_range = CodeRange {
+            start: self.map_position(&err.code_range.start),
+            end: self.map_position(&err.code_range.end),
+        };
+        // The note of the innermost macro definition is kept.
+        let note = err.note.or_else(|| self.note.clone());
+        let fix = match err.fix {
+            Some(fix) if !self.placeholders => {
+                let Fix {
+                    message,
+                    applicability,
+                    edits,
+                } = *fix;
+                let edits = edits
+                    .into_iter()
+                    .map(|edit| TextEdit {
+                        start: self.map_byte(edit.start),
+                        end: self.map_byte(edit.end),
+                        ..edit
+                    })
+                    .collect();
+                Some(Box::new(Fix::new(message, applicability, edits)))
+            }
+            _ => None,
+        };
+        ValidationError {
+            code_range,
+            note,
+            fix,
+            ..err
+        }
+    }
+
+    fn map_position(&self, pos: &CodePosition) -> CodePosition {
+        let row = pos.row.saturating_sub(self.offset.row);
+        CodePosition {
+            row: self.start.row + row,
+            column: if row == 0 {
+                self.start.column + pos.column.saturating_sub(self.offset.column)
+            } else {
+                pos.column
+            },
+            byte: self.map_byte(pos.byte),
+        }
+    }
+
+    fn map_byte(&self, byte: usize) -> usize {
+        self.start.byte + byte.saturating_sub(self.offset.byte)
+    }
+}
+
+fn parse_range(start: usize, end: usize, source: &str) -> Option<Fragment> {
+    Fragment::parse(source.get(start..end)?, position_of(source, start))
+}
+
+pub fn macro_fragments(invocation: &Node, source: &str) -> Vec<Fragment> {
+    let tree = match (0..invocation.named_child_count())
+        .filter_map(|i| invocation.named_child(i))
+        .find(|child| child.kind() == TOKEN_TREE)
+    {
+        Some(tree) => tree,
+        None => return vec![],
+    };
+    let (args, separators) = split_arguments(&tree, source);
+    if args.is_empty() {
+        return vec![];
+    }
+    if let Some(fragment) = Fragment::splice(invocation, &tree, &args, &separators, source) {
+        return vec![fragment];
+    }
+
+    // Otherwise each argument is parsed on its own to find the expressions.
+    let mut kept = vec![];
+    let mut fragments = vec![];
+    for (start, end) in args {
+        if let Some(fragment) = parse_range(start, end, source) {
+            kept.push((start, end));
+            fragments.push(fragment);
+            continue;
+        }
+        // Arguments like `res = fut => { .. }` in `tokio::select!` are not expressions,
+        // but the blocks inside them are.
+        for i in 0..tree.named_child_count() {
+            let block = match tree.named_child(i) {
+                Some(child) if child.kind() == TOKEN_TREE => child,
+                _ => continue,
+            };
+            if block.start_byte() < start || block.end_byte() > end {
+                continue;
+            }
+            if !node_text(&block, source).starts_with('{') {
+                continue;
+            }
+            if let Some(fragment) = parse_range(block.start_byte(), block.end_byte(), source) {
+                kept.push((block.start_byte(), block.end_byte()));
+                fragments.push(fragment);
+            }
+        }
+    }
+    if kept.is_empty() {
+        return fragments;
+    }
+    // The expressions are still spliced together when they're inside a function.
+    match Fragment::splice(invocation, &tree, &kept, &separators, source) {
+        Some(fragment) => vec![fragment],
+        None => fragments,
+    }
+}
+
+// Each transcriber is parsed as a block with
+// - the metavariables `$e` replaced by the placeholders `_e`,
+// - the repetitions `$( .. ),*` replaced by the blocks `{ .. }`.
+// The replacements keep the length of the code so that the positions still match.
+pub fn macro_rule_fragments(definition: &Node, source: &str) -> Vec<Fragment> {
+    let name = match definition.child_by_field_name(NAME) {
+        Some(name) => node_text(&name, source),
+        None => return vec![],
+    };
+    let mut fragments = vec![];
+    for rule in find_descendants(definition, MACRO_RULE) {
+        let body = match rule.child_by_field_name(RIGHT) {
+            Some(body) => body,
+            None => continue,
+        };
+        let text = match replace_metavariables(&body, source) {
+            Some(text) => text,
+            None => continue,
+        };
+        let start = position_of(source, body.start_byte());
+        if let Some(mut fragment) = Fragment::parse(&text, start) {
+            fragment.note = Some(format!("in the definition of macro `{}!`", name));
+            fragment.placeholders = true;
+            fragments.push(fragment);
+        }
+    }
+    fragments
+}
+
+fn replace_metavariables(body: &Node, source: &str) -> Option<String> {
+    let offset = body.start_byte();
+    let mut bytes = node_text(body, source).as_bytes().to_vec();
+    let last = bytes.len().checked_sub(1)?;
+    // The transcriber could also be delimited by `()` or `[]`.
+    *bytes.get_mut(0)? = b'{';
+    *bytes.get_mut(last)? = b'}';
+
+    for var in find_descendants(body, METAVARIABLE) {
+        *bytes.get_mut(var.start_byte() - offset)? = b'_';
+    }
+    for repetition in find_descendants(body, TOKEN_REPETITION) {
+        // `$`, `(`, the repeated tokens, `)`, then the separator and the operator.
+        let close = (0..repetition.child_count())
+            .rev()
+            .filter_map(|i| repetition.child(i))
+            .find(|child| !child.is_named() && node_text(child, source) == ")")?;
+        let start = repetition.start_byte() - offset;
+        *bytes.get_mut(start)? = b' ';
+        *bytes.get_mut(start + 1)? = b'{';
+        *bytes.get_mut(close.start_byte() - offset)? = b'}';
+        for i in close.end_byte()..repetition.end_byte() {
+            *bytes.get_mut(i - offset)? = b' ';
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+// Returns the byte ranges of the top level arguments, and the positions of the `,` or `;`
+// separating them.
+fn split_arguments(tree: &Node, source: &str) -> (Vec<(usize, usize)>, Vec<usize>) {
+    // The nested token trees, literals and comments are skipped as a whole.
+    let opaque: Vec<(usize, usize)> = (0..tree.named_child_count())
+        .filter_map(|i| tree.named_child(i))
+        .map(|child| (child.start_byte(), child.end_byte()))
+        .collect();
+    let is_opaque = |i: usize| opaque.iter().any(|&(start, end)| start <= i && i < end);
+
+    let bytes = source.as_bytes();
+    let inner_end = tree.end_byte().saturating_sub(1);
+    let mut args = vec![];
+    let mut separators = vec![];
+    let mut arg_start = tree.start_byte() + 1;
+    // The depth of the generic arguments of a turbofish like `f::<A, B>(x)`.
+    let mut generics = 0;
+    let mut i = arg_start;
+    while i < inner_end {
+        if let Some(&(_, end)) = opaque.iter().find(|(start, _)| *start == i) {
+            i = end;
+            continue;
+        }
+        match bytes.get(i) {
+            Some(b'<') if generics > 0 || source.get(..i).is_some_and(|s| s.ends_with("::")) => {
+                generics += 1;
+            }
+            // Not the `->` of a function pointer type.
+            Some(b'>') if generics > 0 && bytes.get(i - 1) != Some(&b'-') => generics -= 1,
+            // The parameters of a closure like `|a, b| a + b` are skipped up to the closing `|`.
+            Some(b'|') if generics == 0 && starts_closure(source, arg_start, i) => {
+                if let Some(close) =
+                    (i + 1..inner_end).find(|&j| bytes.get(j) == Some(&b'|') && !is_opaque(j))
+                {
+                    i = close;
+                }
+            }
+            Some(b',') | Some(b';') if generics == 0 => {
+                args.push((arg_start, i));
+                separators.push(i);
+                arg_start = i + 1;
+            }
+            None => break,
+            _ => (),
+        }
+        i += 1;
+    }
+    args.push((arg_start, inner_end));
+
+    let args = args
+        .into_iter()
+        .filter_map(|(start, end)| trim(source, start, end))
+        .collect();
+    (args, separators)
+}
+
+// A `|` starts a closure at the start of an expression, but is a binary operator after an operand.
+fn starts_closure(source: &str, arg_start: usize, i: usize) -> bool {
+    let before = source.get(arg_start..i).unwrap_or("").trim_end();
+    before.is_empty() || before.ends_with("move") || before.ends_with('=') || before.ends_with("=>")
+}
+
+fn trim(source: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+    let text = source.get(start..end)?;
+    let trimmed_start = text.trim_start();
+    if trimmed_start.is_empty() {
+        return None;
+    }
+    let start = start + (text.len() - trimmed_start.len());
+    let end = start + trimmed_start.trim_end().len();
+    Some((start, end))
+}
+
+fn position_of(source: &str, byte: usize) -> CodePosition {
+    let before = source.get(..byte).unwrap_or("");
+    let row = before.matches('\n').count();
+    let column = match before.rfind('\n') {
+        Some(newline) => byte - newline - 1,
+        None => byte,
+    };
+    CodePosition { row, column, byte }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::filters::filter_nothing::NothingFilter;
+    use crate::{AllRulesValidator, LintConfig, RuleCode, Validator};
+
+    fn validate_all(source_code: &str) -> Result<(), ValidationError> {
+        let source = assert_some!(SourceCode::parse(source_code));
+        let validator =
+            AllRulesValidator::new(vec![], Box::new(NothingFilter), &LintConfig::default());
+        validator.validate(&source.get_root_node(), source_code)
+    }
+
+    #[test]
+    fn test_macro_arguments() {
+        let source_code = "fn f() {\n    println!(\"{}, {}\", a, x.unwrap());\n}";
+        let err = assert_err!(validate_all(source_code));
+        assert_eq!(err.rule.code, RuleCode::Unwrap);
+        assert_eq!(err.code_range.start.row, 1);
+        assert_eq!(err.code_range.start.column, 28);
+        assert_eq!(err.code_range.start.byte, 37);
+        assert_eq!(source_code.get(37..43), Some("unwrap"));
+    }
+
+    #[test]
+    fn test_multiline_arguments() {
+        let source_code = "fn f() {\n    assert!(\n        ok,\n        \"{}\",\n        v[i]);\n}";
+        let err = assert_err!(validate_all(source_code));
+        assert_eq!(err.rule.code, RuleCode::IndexExpression);
+        assert_eq!(err.code_range.start.row, 4);
+        assert_eq!(err.code_range.start.column, 8);
+    }
+
+    #[test]
+    fn test_nested_macros() {
+        let source_code = "fn f() { vec![format!(\"{}\", a.expect(\"\"))]; }";
+        let err = assert_err!(validate_all(source_code));
+        assert_eq!(err.rule.code, RuleCode::Expect);
+        assert_eq!(err.code_range.start.column, 30);
+    }
+
+    #[test]
+    fn test_enclosing_function() {
+        let source_code =
+            "fn f(v: Vec<u8>, i: usize) { if i < v.len() { println!(\"{}\", v[i]); } }";
+        assert!(validate_all(source_code).is_ok());
+        let source_code = "fn f() { let a = [0u8; 4]; println!(\"{}\", a[2]); }";
+        assert!(validate_all(source_code).is_ok());
+        let source_code = "fn f() { let p: Parser = Parser::new(); assert!(p.expect(Tok::A)); }";
+        assert!(validate_all(source_code).is_ok());
+
+        let source_code = "fn f(v: Vec<u8>, i: usize) {\n    if ok { println!(\"{}\", v[i]); }\n}";
+        let err = assert_err!(validate_all(source_code));
+        assert_eq!(err.rule.code, RuleCode::IndexExpression);
+        assert_eq!(err.code_range.start.row, 1);
+        assert_eq!(err.code_range.start.column, 27);
+        assert_eq!(
+            source_code.get(err.code_range.start.byte..err.code_range.end.byte),
+            Some("v[i]")
+        );
+    }
+
+    #[test]
+    fn test_split_arguments() {
+        let source_code = "fn f() { m!(f::<A, B>(x), |a, b| a | b, move || c, d); }";
+        let source = assert_some!(SourceCode::parse(source_code));
+        let trees = find_descendants(&source.get_root_node(), TOKEN_TREE);
+        let tree = assert_some!(trees.first());
+        let (args, separators) = split_arguments(tree, source_code);
+        let args: Vec<_> = args
+            .into_iter()
+            .filter_map(|(start, end)| source_code.get(start..end))
+            .collect();
+        assert_eq!(args, vec!["f::<A, B>(x)", "|a, b| a | b", "move || c", "d"]);
+        assert_eq!(separators.len(), 3);
+
+        let source_code = "fn f() { println!(\"{}\", f::<A, B>(x.unwrap())); }";
+        let err = assert_err!(validate_all(source_code));
+        assert_eq!(err.rule.code, RuleCode::Unwrap);
+        let source_code = "fn f() { m!(|a, b| a.unwrap()); }";
+        let err = assert_err!(validate_all(source_code));
+        assert_eq!(err.rule.code, RuleCode::Unwrap);
+    }
+
+    #[test]
+    fn test_select_blocks() {
+        let source_code = "fn f() { select! { v = rx => { v.unwrap() }, } }";
+        let err = assert_err!(validate_all(source_code));
+        assert_eq!(err.rule.code, RuleCode::Unwrap);
+        assert_eq!(err.code_range.start.column, 33);
+        // The blocks are still spliced into the enclosing function.
+        let source_code =
+            "fn f(v: Vec<u8>, i: usize) { if i < v.len() { select! { x = rx => { v[i] }, } } }";
+        assert!(validate_all(source_code).is_ok());
+        let source_code =
+            "fn f(v: Vec<u8>, i: usize) { if ok { select! { x = rx => { v[i] }, } } }";
+        let err = assert_err!(validate_all(source_code));
+        assert_eq!(err.rule.code, RuleCode::IndexExpression);
+    }
+
+    #[test]
+    fn test_macro_rules() {
+        let source_code = "macro_rules! get {\n    ($e:expr) => { $e.unwrap() };\n}";
+        let err = assert_err!(validate_all(source_code));
+        assert_eq!(err.rule.code, RuleCode::Unwrap);
+        assert_eq!(err.code_range.start.row, 1);
+        assert_eq!(err.code_range.start.column, 22);
+        assert_eq!(
+            err.note.as_deref(),
+            Some("in the definition of macro `get!`")
+        );
+    }
+
+    #[test]
+    fn test_macro_rules_repetitions() {
+        let source_code = r#"
+            macro_rules! read_all {
+                ($($ptr:expr),*) => {{ $( v.push($ptr.len()); )* unsafe { $crate::read() } }};
+            }
+        "#;
+        let err = assert_err!(validate_all(source_code));
+        assert_eq!(err.rule.code, RuleCode::Unsafe);
+        assert_eq!(
+            err.note.as_deref(),
+            Some("in the definition of macro `read_all!`")
+        );
+        let source_code = "macro_rules! m { ($($x:expr),*) => { vec![$($x),*] }; }";
+        assert!(validate_all(source_code).is_ok());
+    }
+
+    #[test]
+    fn test_not_expressions() {
+        let source_code = "fn f() { m!(=> unwrap); }";
+        assert!(validate_all(source_code).is_ok());
+    }
+}
@@ -3,13 +3,23 @@ pub mod utils;
 
 mod core;
 pub use self::core::{
-    default_filter, AllRulesValidator, Filter, SourceCode, ValidationError, Validator,
+    default_filter, AllRulesValidator, CodePosition, CodeRange, Filter, LintConfig, SourceCode,
+    ValidationError, Validator,
 };
+mod diff;
+pub use self::diff::unified_diff;
+pub mod fix;
 mod iter;
 pub use self::iter::NodeIterator;
 mod grammar;
+mod guard;
 mod hint;
+mod infer;
+mod literal;
+mod macros;
 mod rule;
-pub use self::rule::{Rule, RuleCode, RULES};
+mod suggest;
+pub use self::rule::{find_rule, Rule, RuleCode, Severity, RULES};
 pub mod filters;
 pub mod validators;
+pub use self::validators::expect_call::ExpectReceivers;
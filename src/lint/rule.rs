@@ -1,4 +1,5 @@
 use super::hint;
+use std::fmt;
 
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
 pub enum RuleCode {
@@ -7,6 +8,7 @@ pub enum RuleCode {
     Unwrap,
     Expect,
     IndexExpression,
+    InvalidLiteral,
 }
 
 impl ToString for RuleCode {
@@ -15,43 +17,96 @@ impl ToString for RuleCode {
     }
 }
 
+// Warnings can be suppressed while errors are certain panics and can't be suppressed.
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct Rule {
     pub code: RuleCode,
+    pub severity: Severity,
     pub desc: &'static str,
     pub hint: Option<&'static str>,
 }
 
 pub static RULE_UNSAFE_CODE: Rule = Rule {
     code: RuleCode::Unsafe,
+    severity: Severity::Warning,
     desc: "Unsafe keyword is forbidden.",
     hint: None,
 };
 pub static RULE_USE_UNSAFE: Rule = Rule {
     code: RuleCode::UseUnsafe,
+    severity: Severity::Warning,
     desc: "using unsafe identifier like function or macro is forbidden.",
     hint: Some(hint::UNSAFE_HINT),
 };
 pub static RULE_UNWRAP_CALL: Rule = Rule {
     code: RuleCode::Unwrap,
+    severity: Severity::Warning,
     desc: "Unwrap call may panic.",
     hint: Some(hint::FUNCTOR_HINT),
 };
 pub static RULE_EXPECT_CALL: Rule = Rule {
     code: RuleCode::Expect,
+    severity: Severity::Warning,
     desc: "expect call may panic.",
     hint: Some(hint::FUNCTOR_HINT),
 };
 pub static RULE_INDEX_EXPRESSION: Rule = Rule {
     code: RuleCode::IndexExpression,
+    severity: Severity::Warning,
     desc: "index operation may panic, use get method instead.",
     hint: Some(hint::INDEX_EXPR_HINT),
 };
+pub static RULE_INDEX_SLICE: Rule = Rule {
+    code: RuleCode::IndexExpression,
+    severity: Severity::Warning,
+    desc: "slicing may panic when the range is out of bounds or not on a char boundary, use get method instead.",
+    hint: Some(hint::SLICE_EXPR_HINT),
+};
+pub static RULE_INDEX_MAP: Rule = Rule {
+    code: RuleCode::IndexExpression,
+    severity: Severity::Warning,
+    desc: "indexing a map panics when the key doesn't exist, use get method instead.",
+    hint: Some(hint::MAP_INDEX_HINT),
+};
+pub static RULE_INVALID_LITERAL: Rule = Rule {
+    code: RuleCode::InvalidLiteral,
+    severity: Severity::Error,
+    desc: "parsing this literal always fails so the unwrap or expect call will panic.",
+    hint: Some(hint::INVALID_LITERAL_HINT),
+};
 
-pub static RULES: [Rule; 5] = [
+pub static RULES: [Rule; 6] = [
     RULE_UNSAFE_CODE,
     RULE_USE_UNSAFE,
     RULE_UNWRAP_CALL,
     RULE_EXPECT_CALL,
     RULE_INDEX_EXPRESSION,
+    RULE_INVALID_LITERAL,
 ];
+
+// The variants sharing a code with the rules above.
+static RULE_VARIANTS: [&Rule; 2] = [&RULE_INDEX_SLICE, &RULE_INDEX_MAP];
+
+// Find a rule by its code and description, including the variants sharing the code.
+pub fn find_rule(code: &str, desc: &str) -> Option<Rule> {
+    RULES
+        .iter()
+        .chain(RULE_VARIANTS.iter().copied())
+        .find(|rule| rule.code.to_string() == code && rule.desc == desc)
+        .copied()
+}
@@ -0,0 +1,175 @@
+// Suggest replacing `unwrap` and `expect` with `?`
+// when the enclosing function or closure returns an Option or a Result.
+use tree_sitter::Node;
+
+use crate::lint::core::ValidationError;
+use crate::lint::fix::{Applicability, Fix, TextEdit};
+use crate::lint::grammar::{
+    ARGUMENTS, ASYNC_BLOCK, CALL_EXPRESSION, CLOSURE_EXPRESSION, FIELD_EXPRESSION, FUNCTION,
+    FUNCTION_ITEM, STRING_LITERAL, VALUE,
+};
+use crate::lint::infer::{infer_declared, infer_expression, infer_return_type, TypeClass};
+use crate::lint::rule::Rule;
+use crate::lint::utils::{find_ancestor, node_text};
+
+// The concrete rewrite replaces the generic hint of the rule.
+pub fn unwrap_error(method: &Node, source: &str, rule: Rule) -> ValidationError {
+    match suggest_try(method, source) {
+        Some(fix) => ValidationError::from_node(method, Rule { hint: None, ..rule }).with_fix(fix),
+        None => ValidationError::from_node(method, rule),
+    }
+}
+
+// `method` is the `unwrap` or `expect` field identifier.
+pub fn suggest_try(method: &Node, source: &str) -> Option<Fix> {
+    let field_expr = method.parent().filter(|p| p.kind() == FIELD_EXPRESSION)?;
+    let call = field_expr
+        .parent()
+        .filter(|p| p.kind() == CALL_EXPRESSION)?;
+    if call.child_by_field_name(FUNCTION) != Some(field_expr) {
+        return None;
+    }
+    // `?` in an async block returns from the block instead of the function.
+    match find_ancestor(method, &[FUNCTION_ITEM, CLOSURE_EXPRESSION, ASYNC_BLOCK]) {
+        Some(scope) if scope.kind() != ASYNC_BLOCK => (),
+        _ => return None,
+    }
+
+    let receiver = field_expr.child_by_field_name(VALUE)?;
+    let expr = node_text(&receiver, source);
+    let message = expect_message(&call, source);
+
+    use Applicability::{MachineApplicable, Suggestion};
+    let (replacement, applicability) = match (
+        infer_expression(&receiver, source),
+        infer_return_type(method, source),
+    ) {
+        // A class guessed from a method name like `get` could be wrong for a type of the user.
+        (TypeClass::Option, TypeClass::Option) => {
+            let applicability = match infer_declared(&receiver, source) {
+                TypeClass::Option => MachineApplicable,
+                _ => Suggestion,
+            };
+            (format!("{}?", expr), applicability)
+        }
+        // The error type might not be convertible.
+        (TypeClass::Result, TypeClass::Result) => (format!("{}?", expr), Suggestion),
+        (TypeClass::Unknown, class) if class.is_option_or_result() => {
+            (format!("{}?", expr), Suggestion)
+        }
+        (TypeClass::Result, TypeClass::Option) => (format!("{}.ok()?", expr), Suggestion),
+        (TypeClass::Option, TypeClass::Result) => {
+            let convert = match message {
+                Some(msg) if msg.kind() == STRING_LITERAL => {
+                    format!("ok_or({})", node_text(&msg, source))
+                }
+                Some(msg) => format!("ok_or_else(|| {})", node_text(&msg, source)),
+                None => "ok_or_else(|| /* error */)".to_string(),
+            };
+            (format!("{}.{}?", expr, convert), Suggestion)
+        }
+        _ => return None,
+    };
+
+    let edit = TextEdit::new(call.start_byte(), call.end_byte(), replacement.clone());
+    Some(Fix::new(
+        format!("replace it with `{}`", replacement),
+        applicability,
+        vec![edit],
+    ))
+}
+
+// The message of `expect("message")`.
+fn expect_message<'a>(call: &Node<'a>, source: &str) -> Option<Node<'a>> {
+    let args = (0..call.named_child_count())
+        .filter_map(|i| call.named_child(i))
+        .find(|child| child.kind() == ARGUMENTS)?;
+    let msg = args.named_child(0)?;
+    // Skip the ones like `expect(&format!(..))` that need the message to be alive.
+    if node_text(&msg, source).starts_with('&') {
+        return None;
+    }
+    Some(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::grammar::FIELD_IDENTIFIER;
+    use crate::lint::utils::find_descendants;
+    use crate::SourceCode;
+
+    fn suggest(source_code: &str) -> Option<(String, Applicability)> {
+        let source = assert_some!(SourceCode::parse(source_code));
+        let root = source.get_root_node();
+        let method = assert_some!(find_descendants(&root, FIELD_IDENTIFIER).pop());
+        suggest_try(&method, source_code).map(|fix| {
+            let edit = assert_some!(fix.edits.into_iter().next());
+            let mut fixed = source_code.to_string();
+            fixed.replace_range(edit.start..edit.end, &edit.replacement);
+            (fixed, fix.applicability)
+        })
+    }
+
+    #[test]
+    fn test_option_in_option() {
+        let source_code = "fn f(a: Option<u8>) -> Option<u8> { let b = a.unwrap(); Some(b) }";
+        let (fixed, applicability) = assert_some!(suggest(source_code));
+        assert_eq!(
+            fixed,
+            "fn f(a: Option<u8>) -> Option<u8> { let b = a?; Some(b) }"
+        );
+        assert_eq!(applicability, Applicability::MachineApplicable);
+    }
+
+    #[test]
+    fn test_guessed_option() {
+        // `Map::get` is defined in another file and could return a Result.
+        let source_code = "fn f(m: &Map) -> Option<u8> { let v = m.get(1).unwrap(); Some(v) }";
+        let (fixed, applicability) = assert_some!(suggest(source_code));
+        assert_eq!(
+            fixed,
+            "fn f(m: &Map) -> Option<u8> { let v = m.get(1)?; Some(v) }"
+        );
+        assert_eq!(applicability, Applicability::Suggestion);
+
+        // It's known to return a Result when it's defined in the same file.
+        let source_code = r#"
+            impl Map { fn get(&self, k: u8) -> Result<u8, E> { Ok(k) } }
+            fn f(m: &Map) -> Option<u8> { let v = m.get(1).unwrap(); Some(v) }
+        "#;
+        let (fixed, applicability) = assert_some!(suggest(source_code));
+        assert!(fixed.contains("let v = m.get(1).ok()?;"), "{}", fixed);
+        assert_eq!(applicability, Applicability::Suggestion);
+
+        let source_code =
+            "fn f(a: Option<u8>) -> Option<u8> { let b = a.map(g).unwrap(); Some(b) }";
+        let (_, applicability) = assert_some!(suggest(source_code));
+        assert_eq!(applicability, Applicability::MachineApplicable);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let source_code = r#"fn f(a: Option<u8>) -> Result<u8, E> { Ok(a.expect("no a")) }"#;
+        let (fixed, applicability) = assert_some!(suggest(source_code));
+        assert_eq!(
+            fixed,
+            r#"fn f(a: Option<u8>) -> Result<u8, E> { Ok(a.ok_or("no a")?) }"#
+        );
+        assert_eq!(applicability, Applicability::Suggestion);
+
+        let source_code = "fn f(a: Result<u8, E>) -> Option<u8> { Some(a.unwrap()) }";
+        let (fixed, _) = assert_some!(suggest(source_code));
+        assert_eq!(
+            fixed,
+            "fn f(a: Result<u8, E>) -> Option<u8> { Some(a.ok()?) }"
+        );
+    }
+
+    #[test]
+    fn test_no_suggestion() {
+        assert!(suggest("fn f(a: Option<u8>) { a.unwrap(); }").is_none());
+        assert!(suggest("fn f() -> Option<u8> { async { a.unwrap() } }").is_none());
+        assert!(suggest("fn f() -> Option<u8> { let g = |a| a.unwrap(); }").is_none());
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{Filter, NodeIterator, SourceCode, ValidationError, Validator};
 use tree_sitter::Node;
 
-#[allow(unused_macros)]
+// Exported for the tests of the binary.
+#[doc(hidden)]
+#[macro_export]
 macro_rules! assert_some {
     ($expression:expr) => {
         match $expression {
@@ -11,7 +13,9 @@ macro_rules! assert_some {
     };
 }
 
-#[allow(unused_macros)]
+// Exported for the tests of the binary.
+#[doc(hidden)]
+#[macro_export]
 macro_rules! assert_err {
     ($expression:expr) => {
         match $expression {
@@ -21,6 +25,18 @@ macro_rules! assert_err {
     };
 }
 
+// Exported for the tests of the binary.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! assert_ok {
+    ($expression:expr) => {
+        match $expression {
+            Ok(item) => item,
+            Err(err) => panic!("assertion failed: {:?} is not ok", err),
+        }
+    };
+}
+
 fn recur_validate(
     node: &Node,
     source: &str,
@@ -61,6 +77,58 @@ fn get_text<'a>(node_kind: &str, node: &Node, source: &'a str) -> Option<&'a str
     None
 }
 
+pub fn node_text<'a>(node: &Node, source: &'a str) -> &'a str {
+    match node.utf8_text(source.as_bytes()) {
+        Ok(text) => text,
+        Err(err) => {
+            error!("failed to get node text: {:?}", err);
+            ""
+        }
+    }
+}
+
+pub fn find_descendants<'a>(node: &Node<'a>, node_kind: &str) -> Vec<Node<'a>> {
+    let mut found = vec![];
+    let mut cursor = node.walk();
+    let mut visited_children = false;
+    loop {
+        if !visited_children {
+            if cursor.node().kind() == node_kind {
+                found.push(cursor.node());
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+        }
+        if cursor.goto_next_sibling() {
+            visited_children = false;
+        } else if cursor.goto_parent() {
+            visited_children = true;
+        } else {
+            return found;
+        }
+    }
+}
+
+pub fn find_ancestor<'a>(node: &Node<'a>, node_kinds: &[&str]) -> Option<Node<'a>> {
+    let mut curr = node.parent();
+    while let Some(n) = curr {
+        if node_kinds.contains(&n.kind()) {
+            return Some(n);
+        }
+        curr = n.parent();
+    }
+    None
+}
+
+pub fn get_root<'a>(node: &Node<'a>) -> Node<'a> {
+    let mut root = *node;
+    while let Some(parent) = root.parent() {
+        root = parent;
+    }
+    root
+}
+
 #[allow(dead_code)]
 pub fn assert_source_ok(source_code: &str, validator: Box<dyn Validator>, filter: &dyn Filter) {
     let res = validate(source_code, validator, filter);
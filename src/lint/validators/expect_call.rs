@@ -1,16 +1,73 @@
+use std::str::FromStr;
 use tree_sitter::Node;
 
 use crate::lint::core::{ValidationError, Validator};
-use crate::lint::grammar::FIELD_IDENTIFIER;
-use crate::lint::rule::RULE_EXPECT_CALL;
+use crate::lint::grammar::{FIELD_IDENTIFIER, VALUE};
+use crate::lint::infer::{infer_expression, TypeClass};
+use crate::lint::literal::{check_literal_parse, LiteralParse};
+use crate::lint::rule::{RULE_EXPECT_CALL, RULE_INVALID_LITERAL};
+use crate::lint::suggest::unwrap_error;
 use crate::lint::utils::node_lowercase_eq;
 
-pub struct ExpectCallValidator;
+// Which `expect` receivers should be reported.
+// The receivers inferred as user types are never reported
+// since they could have their own `expect` method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExpectReceivers {
+    // Only the receivers known to be Option or Result.
+    Known,
+    // Also the receivers whose type can't be inferred.
+    #[default]
+    Unknown,
+}
+
+impl FromStr for ExpectReceivers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "known" => Ok(ExpectReceivers::Known),
+            "unknown" => Ok(ExpectReceivers::Unknown),
+            other => Err(format!("invalid expect receivers: {}", other)),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct ExpectCallValidator {
+    receivers: ExpectReceivers,
+}
+
+impl ExpectCallValidator {
+    pub fn new(receivers: ExpectReceivers) -> Self {
+        Self { receivers }
+    }
+}
 
 impl Validator for ExpectCallValidator {
     fn validate(&self, node: &Node, source: &str) -> Result<(), ValidationError> {
-        if node_lowercase_eq(FIELD_IDENTIFIER, node, source, "expect") {
-            Err(ValidationError::from_node(node, RULE_EXPECT_CALL))
+        if !node_lowercase_eq(FIELD_IDENTIFIER, node, source, "expect") {
+            return Ok(());
+        }
+        match check_literal_parse(node, source) {
+            LiteralParse::Valid => return Ok(()),
+            LiteralParse::Invalid(literal) => {
+                return Err(ValidationError::from_node(&literal, RULE_INVALID_LITERAL))
+            }
+            LiteralParse::Unknown => (),
+        }
+
+        let class = match node.parent().and_then(|p| p.child_by_field_name(VALUE)) {
+            Some(receiver) => infer_expression(&receiver, source),
+            None => TypeClass::Unknown,
+        };
+        let report = match class {
+            TypeClass::Option | TypeClass::Result => true,
+            TypeClass::UserType => false,
+            TypeClass::Unknown => self.receivers == ExpectReceivers::Unknown,
+        };
+        if report {
+            Err(unwrap_error(node, source, RULE_EXPECT_CALL))
         } else {
             Ok(())
         }
@@ -28,15 +85,69 @@ mod tests {
     fn test_no_expect_call() {
         let filter = NothingFilter;
         let source_code = "fn test() -> usize { a.no_expect_call() }";
-        assert_source_ok(source_code, Box::new(ExpectCallValidator), &filter);
+        assert_source_ok(
+            source_code,
+            Box::new(ExpectCallValidator::default()),
+            &filter,
+        );
     }
 
     #[test]
     fn test_expect_call() {
         let filter = NothingFilter;
         let source_code = "fn test() -> usize { a.expect() }";
-        let res = validate(source_code, Box::new(ExpectCallValidator), &filter);
+        let res = validate(
+            source_code,
+            Box::new(ExpectCallValidator::default()),
+            &filter,
+        );
         let err = assert_err!(res);
         assert_eq!(err.rule.code, RuleCode::Expect);
     }
+
+    #[test]
+    fn test_user_defined_expect() {
+        let filter = NothingFilter;
+        let source_code = r#"
+            impl Parser {
+                fn parse(&mut self) { self.expect(Token::LParen); }
+            }
+        "#;
+        assert_source_ok(
+            source_code,
+            Box::new(ExpectCallValidator::default()),
+            &filter,
+        );
+    }
+
+    #[test]
+    fn test_known_receivers() {
+        let filter = NothingFilter;
+        let validator = ExpectCallValidator::new(ExpectReceivers::Known);
+        let source_code = "fn test() -> usize { a.expect(\"\") }";
+        assert_source_ok(source_code, Box::new(validator), &filter);
+
+        let source_code = "fn test(a: Option<usize>) -> usize { a.expect(\"\") }";
+        let res = validate(source_code, Box::new(validator), &filter);
+        let err = assert_err!(res);
+        assert_eq!(err.rule.code, RuleCode::Expect);
+
+        // The methods with the std names defined in the file don't hide the std ones.
+        let source_code = r#"
+            impl Lexer {
+                fn next(&mut self) -> Token { Token::Eof }
+                fn peek(&mut self) -> char { self.chars.next().expect("eof") }
+            }
+        "#;
+        let res = validate(source_code, Box::new(validator), &filter);
+        assert_eq!(assert_err!(res).rule.code, RuleCode::Expect);
+        let source_code = r#"
+            impl Parser {
+                fn parse(&mut self) {}
+                fn port(&self, s: &str) -> u16 { s.parse::<u16>().expect("port") }
+            }
+        "#;
+        let res = validate(source_code, Box::new(validator), &filter);
+        assert_eq!(assert_err!(res).rule.code, RuleCode::Expect);
+    }
 }
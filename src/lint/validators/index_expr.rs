@@ -1,20 +1,188 @@
 use tree_sitter::Node;
 
 use crate::lint::core::{ValidationError, Validator};
-use crate::lint::grammar::INDEX_EXPRESSION;
-use crate::lint::rule::RULE_INDEX_EXPRESSION;
+use crate::lint::fix::{Applicability, Fix, TextEdit};
+use crate::lint::grammar::{
+    ARRAY_EXPRESSION, ARRAY_TYPE, CALL_EXPRESSION, FUNCTION, INDEX_EXPRESSION, INTEGER_LITERAL,
+    LENGTH, PATH, RANGE_EXPRESSION, REFERENCE_EXPRESSION, REFERENCE_TYPE, SCOPED_IDENTIFIER, TYPE,
+};
+use crate::lint::guard::is_bounds_checked;
+use crate::lint::infer::{declared_type, initializer, last_segment, type_name};
+use crate::lint::rule::{Rule, RULE_INDEX_EXPRESSION, RULE_INDEX_MAP, RULE_INDEX_SLICE};
+use crate::lint::utils::node_text;
+
+const MAP_TYPES: [&str; 3] = ["HashMap", "BTreeMap", "IndexMap"];
 
 pub struct IndexExpressionValidator;
 
 impl Validator for IndexExpressionValidator {
-    fn validate(&self, node: &Node, _source: &str) -> Result<(), ValidationError> {
+    fn validate(&self, node: &Node, source: &str) -> Result<(), ValidationError> {
         if node.kind() != INDEX_EXPRESSION {
             return Ok(());
         }
-        Err(ValidationError::from_node(node, RULE_INDEX_EXPRESSION))
+        match classify(node, source) {
+            Some(rule) => {
+                let err = ValidationError::from_node(node, rule);
+                match get_fix(node, source) {
+                    Some(fix) => Err(err.with_fix(fix)),
+                    None => Err(err),
+                }
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+// `v[i]` => `v.get(i)` and `&s[a..b]` => `s.get(a..b)`.
+// It's only a suggestion since the type changes to Option.
+fn get_fix(node: &Node, source: &str) -> Option<Fix> {
+    let value = node_text(&node.named_child(0)?, source);
+    let index = node_text(&node.named_child(1)?, source);
+    let replacement = format!("{}.get({})", value, index);
+    let target = match node.parent() {
+        Some(parent) if parent.kind() == REFERENCE_EXPRESSION => parent,
+        _ => *node,
+    };
+    let edit = TextEdit::new(target.start_byte(), target.end_byte(), replacement.clone());
+    Some(Fix::new(
+        format!("replace it with `{}`", replacement),
+        Applicability::Suggestion,
+        vec![edit],
+    ))
+}
+
+// Returns None if the index expression can never panic.
+fn classify(node: &Node, source: &str) -> Option<Rule> {
+    let (value, index) = match (node.named_child(0), node.named_child(1)) {
+        (Some(value), Some(index)) => (value, index),
+        _ => return Some(RULE_INDEX_EXPRESSION),
+    };
+
+    if is_map(&value, source) {
+        return Some(RULE_INDEX_MAP);
+    }
+
+    let len = array_length(&value, source);
+    if index.kind() == RANGE_EXPRESSION {
+        // `s[..]` never panics.
+        if index.named_child_count() == 0 {
+            return None;
+        }
+        let (start, end) = range_bounds(&index, source);
+        return match (len, start, end) {
+            (Some(len), Some(start), Some(end)) if start <= end && end <= len => None,
+            (Some(len), Some(start), None) if index.named_child_count() == 1 && start <= len => {
+                None
+            }
+            (Some(len), None, Some(end)) if index.named_child_count() == 1 && end <= len => None,
+            _ => Some(RULE_INDEX_SLICE),
+        };
+    }
+
+    // The compiler rejects the out of bounds constant indices into fixed size arrays.
+    match (len, parse_integer(&index, source)) {
+        (Some(len), Some(i)) if i < len => None,
+        _ if is_bounds_checked(node, source) => None,
+        _ => Some(RULE_INDEX_EXPRESSION),
+    }
+}
+
+fn is_map(value: &Node, source: &str) -> bool {
+    if let Some(ty) = declared_type(value, source) {
+        return match type_name(&strip_reference(ty), source) {
+            Some(name) => MAP_TYPES.contains(&name),
+            None => false,
+        };
+    }
+    // let map = HashMap::new();
+    match initializer(value, source) {
+        Some(init) if init.kind() == CALL_EXPRESSION => init
+            .child_by_field_name(FUNCTION)
+            .filter(|func| func.kind() == SCOPED_IDENTIFIER)
+            .and_then(|func| func.child_by_field_name(PATH))
+            .map(|path| MAP_TYPES.contains(&last_segment(node_text(&path, source))))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+// The length of a local fixed size array like `[u8; 4]`, `[0; 4]` or `[1, 2, 3]`.
+fn array_length(value: &Node, source: &str) -> Option<usize> {
+    if let Some(ty) = declared_type(value, source) {
+        let ty = strip_reference(ty);
+        if ty.kind() != ARRAY_TYPE {
+            return None;
+        }
+        return parse_integer(&ty.child_by_field_name(LENGTH)?, source);
+    }
+    let init = initializer(value, source)?;
+    if init.kind() != ARRAY_EXPRESSION {
+        return None;
+    }
+    match init.child_by_field_name(LENGTH) {
+        Some(len) => parse_integer(&len, source),
+        None => Some(init.named_child_count()),
+    }
+}
+
+// Get `[u8; 4]` from `&[u8; 4]`.
+fn strip_reference(ty: Node) -> Node {
+    match ty.kind() {
+        REFERENCE_TYPE => ty.child_by_field_name(TYPE).unwrap_or(ty),
+        _ => ty,
     }
 }
 
+// Returns the constant start and exclusive end of `a..b`, `a..=b`, `a..` or `..b`.
+fn range_bounds(range: &Node, source: &str) -> (Option<usize>, Option<usize>) {
+    let mut op = None;
+    for i in 0..range.child_count() {
+        match range.child(i) {
+            Some(child) if !child.is_named() => {
+                op = Some(child);
+                break;
+            }
+            _ => (),
+        }
+    }
+    let (op_byte, inclusive) = match op {
+        Some(op) => (op.start_byte(), node_text(&op, source) == "..="),
+        None => return (None, None),
+    };
+    let mut start = None;
+    let mut end = None;
+    for i in 0..range.named_child_count() {
+        if let Some(child) = range.named_child(i) {
+            if child.start_byte() < op_byte {
+                start = parse_integer(&child, source);
+            } else {
+                end = parse_integer(&child, source).and_then(|end| {
+                    if inclusive {
+                        end.checked_add(1)
+                    } else {
+                        Some(end)
+                    }
+                });
+            }
+        }
+    }
+    (start, end)
+}
+
+// Only decimal literals like `2`, `1_000` or `2usize` are supported.
+fn parse_integer(node: &Node, source: &str) -> Option<usize> {
+    if node.kind() != INTEGER_LITERAL {
+        return None;
+    }
+    let text = node_text(node, source);
+    let digits: String = text
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '_')
+        .filter(|c| *c != '_')
+        .collect();
+    digits.parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -22,6 +190,17 @@ mod tests {
     use crate::lint::utils::{assert_source_ok, validate};
     use crate::RuleCode;
 
+    fn assert_rule(source_code: &str, desc: &str) {
+        let res = validate(
+            source_code,
+            Box::new(IndexExpressionValidator),
+            &NothingFilter,
+        );
+        let err = assert_err!(res);
+        assert_eq!(err.rule.code, RuleCode::IndexExpression);
+        assert_eq!(err.rule.desc, desc);
+    }
+
     #[test]
     fn test_no_index_expression() {
         let filter = NothingFilter;
@@ -32,9 +211,85 @@ mod tests {
     #[test]
     fn test_index_expression() {
         let filter = NothingFilter;
-        let source_code = "fn test() -> usize { let a = [0]; a[0] }";
+        let source_code = "fn test(a: Vec<usize>) -> usize { a[0] }";
         let res = validate(source_code, Box::new(IndexExpressionValidator), &filter);
         let err = assert_err!(res);
         assert_eq!(err.rule.code, RuleCode::IndexExpression);
     }
+
+    #[test]
+    fn test_full_range() {
+        let filter = NothingFilter;
+        let source_code = "fn test(s: &str) -> &str { &s[..] }";
+        assert_source_ok(source_code, Box::new(IndexExpressionValidator), &filter);
+    }
+
+    #[test]
+    fn test_fixed_array() {
+        let filter = NothingFilter;
+        let source_code = "fn test() -> usize { let a = [0; 4]; a[3] }";
+        assert_source_ok(source_code, Box::new(IndexExpressionValidator), &filter);
+        let source_code = "fn test(a: &[u8; 4]) -> &[u8] { &a[1..3] }";
+        assert_source_ok(source_code, Box::new(IndexExpressionValidator), &filter);
+        let source_code = "fn test() -> usize { let a = [1, 2]; a[2] }";
+        assert_rule(source_code, RULE_INDEX_EXPRESSION.desc);
+        let source_code = "fn test(a: [u8; 4], i: usize) -> u8 { a[i] }";
+        assert_rule(source_code, RULE_INDEX_EXPRESSION.desc);
+        // Only one of the fields named `buf` is an array.
+        let source_code = r#"
+            struct A { buf: [u8; 4] }
+            struct B { buf: Vec<u8> }
+            fn test(b: B) -> u8 { b.buf[3] }
+        "#;
+        assert_rule(source_code, RULE_INDEX_EXPRESSION.desc);
+    }
+
+    #[test]
+    fn test_bounds_checked() {
+        let filter = NothingFilter;
+        let source_code =
+            "fn test(v: Vec<u8>, i: usize) -> u8 { if i < v.len() { v[i] } else { 0 } }";
+        assert_source_ok(source_code, Box::new(IndexExpressionValidator), &filter);
+        let source_code = "fn test(v: Vec<u8>) -> u8 { if v.is_empty() { return 0; } v[0] }";
+        assert_source_ok(source_code, Box::new(IndexExpressionValidator), &filter);
+    }
+
+    #[test]
+    fn test_slice() {
+        let source_code = "fn test(s: &str) -> &str { &s[..5] }";
+        assert_rule(source_code, RULE_INDEX_SLICE.desc);
+        let source_code = "fn test() -> &[u8] { let a = [0u8; 4]; &a[2..5] }";
+        assert_rule(source_code, RULE_INDEX_SLICE.desc);
+        let source_code = "fn test() -> &[u8] { let a = [0u8; 4]; &a[0..=4] }";
+        assert_rule(source_code, RULE_INDEX_SLICE.desc);
+        let source_code = "fn test() -> &[u8] { let a = [0u8; 4]; &a[1..=4] }";
+        assert_rule(source_code, RULE_INDEX_SLICE.desc);
+        let source_code = "fn test() -> &[u8] { let a = [0u8; 4]; &a[1..=3] }";
+        assert_source_ok(
+            source_code,
+            Box::new(IndexExpressionValidator),
+            &NothingFilter,
+        );
+    }
+
+    #[test]
+    fn test_get_fix() {
+        let filter = NothingFilter;
+        let source_code = "fn test(s: &str) -> &str { &s[..n] }";
+        let res = validate(source_code, Box::new(IndexExpressionValidator), &filter);
+        let fix = assert_some!(assert_err!(res).fix);
+        assert_eq!(fix.applicability, Applicability::Suggestion);
+        assert_eq!(
+            fix.edits,
+            vec![TextEdit::new(27, 34, "s.get(..n)".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        let source_code = "fn test(m: &HashMap<u8, u8>) -> u8 { m[&1] }";
+        assert_rule(source_code, RULE_INDEX_MAP.desc);
+        let source_code = "fn test() -> u8 { let m = BTreeMap::new(); m[&1] }";
+        assert_rule(source_code, RULE_INDEX_MAP.desc);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::lint::core::Validator;
+use crate::lint::core::{LintConfig, Validator};
 
 // #[macro_use]
 // pub mod utils;
@@ -9,12 +9,14 @@ pub mod unsafe_code;
 pub mod unwrap_call;
 pub mod use_unsafe;
 
-pub fn get_all_validators() -> Vec<Box<dyn Validator>> {
+pub fn get_all_validators(config: &LintConfig) -> Vec<Box<dyn Validator>> {
     vec![
         Box::new(unsafe_code::UnsafeCodeValidator),
         Box::new(use_unsafe::UseUnsafeValidator),
         Box::new(unwrap_call::UnwrapCallValidator),
-        Box::new(expect_call::ExpectCallValidator),
+        Box::new(expect_call::ExpectCallValidator::new(
+            config.expect_receivers,
+        )),
         Box::new(index_expr::IndexExpressionValidator),
     ]
 }
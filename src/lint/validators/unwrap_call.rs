@@ -2,17 +2,24 @@ use tree_sitter::Node;
 
 use crate::lint::core::{ValidationError, Validator};
 use crate::lint::grammar::FIELD_IDENTIFIER;
-use crate::lint::rule::RULE_UNWRAP_CALL;
+use crate::lint::literal::{check_literal_parse, LiteralParse};
+use crate::lint::rule::{RULE_INVALID_LITERAL, RULE_UNWRAP_CALL};
+use crate::lint::suggest::unwrap_error;
 use crate::lint::utils::node_lowercase_eq;
 
 pub struct UnwrapCallValidator;
 
 impl Validator for UnwrapCallValidator {
     fn validate(&self, node: &Node, source: &str) -> Result<(), ValidationError> {
-        if node_lowercase_eq(FIELD_IDENTIFIER, node, source, "unwrap") {
-            Err(ValidationError::from_node(node, RULE_UNWRAP_CALL))
-        } else {
-            Ok(())
+        if !node_lowercase_eq(FIELD_IDENTIFIER, node, source, "unwrap") {
+            return Ok(());
+        }
+        match check_literal_parse(node, source) {
+            LiteralParse::Valid => Ok(()),
+            LiteralParse::Invalid(literal) => {
+                Err(ValidationError::from_node(&literal, RULE_INVALID_LITERAL))
+            }
+            LiteralParse::Unknown => Err(unwrap_error(node, source, RULE_UNWRAP_CALL)),
         }
     }
 }
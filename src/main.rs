@@ -1,19 +1,56 @@
 extern crate env_logger;
-use ansi_term::Colour::{Cyan, Green, Red};
-use mylint::{default_filter, AllRulesValidator, SourceCode, ValidationError, Validator, RULES};
-use scan_dir::ScanDir;
+use ansi_term::Colour::{Green, Red};
+use mylint::cache::{Cache, CACHE_DIR};
+use mylint::config::Config;
+use mylint::files::{find_files, generated_marker, FileSelector};
+use mylint::ratchet::{Ratchet, RATCHET_FILE};
+use mylint::report::{get_reporter, ColorChoice, FileReport, Format, Summary, TextOptions};
+use mylint::{
+    default_filter, fix_source, unified_diff, AllRulesValidator, ExpectReceivers, LintConfig,
+    Severity, SourceCode, RULES,
+};
+use std::env;
 use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Instant;
 use structopt::StructOpt;
-use syntect::easy::HighlightLines;
-use syntect::highlighting::{Style, ThemeSet};
-use syntect::parsing::SyntaxSet;
-use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+// The exit codes tell the findings apart from the failures of mylint itself.
+const EXIT_CLEAN: i32 = 0;
+const EXIT_FINDINGS: i32 = 1;
+const EXIT_ERROR: i32 = 2;
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "mylint", about = "Customized rust linter.")]
 struct Opt {
-    #[structopt(short, long, default_value = "./src")]
-    path: String,
+    // The files and directories to lint, `./src` by default.
+    paths: Vec<String>,
+
+    // Kept for the scripts written before the positional paths.
+    #[structopt(short, long)]
+    path: Option<String>,
+
+    // Only lint the files matching these globs.
+    #[structopt(long, number_of_values = 1)]
+    include: Vec<String>,
+
+    // Skip the files and directories matching these globs.
+    #[structopt(long, number_of_values = 1)]
+    exclude: Vec<String>,
+
+    // Also lint the generated files, which are skipped by their header or the config.
+    #[structopt(long)]
+    lint_generated: bool,
+
+    // Lint every file again instead of reading the findings of the unchanged ones from the cache.
+    #[structopt(long)]
+    no_cache: bool,
+
+    // Don't respect `.gitignore` and `.ignore` and lint the hidden files.
+    #[structopt(long)]
+    no_ignore: bool,
 
     #[structopt(short, long)]
     list: bool,
@@ -23,61 +60,92 @@ struct Opt {
 
     #[structopt(short, long)]
     suppress: Vec<String>,
-}
 
-struct LintErr {
-    filepath: String,
-    source_code: String,
-    err: ValidationError,
-}
+    // `known` only reports the `expect` calls on known Option or Result,
+    // `unknown` also reports the ones whose receiver type can't be inferred.
+    #[structopt(long, default_value = "unknown", possible_values = &["known", "unknown"])]
+    expect_receivers: ExpectReceivers,
 
-fn print_err_lines(errs: Vec<LintErr>) -> Result<(), String> {
-    let ps = SyntaxSet::load_defaults_newlines();
-    let ts = ThemeSet::load_defaults();
-
-    let syntax = ps
-        .find_syntax_by_extension("rs")
-        .ok_or_else(|| "failed to load rust syntax".to_string())?;
-    let mut h = HighlightLines::new(
-        syntax,
-        ts.themes
-            .get("base16-ocean.dark")
-            .ok_or_else(|| "failed to get theme".to_string())?,
-    );
-    for LintErr {
-        filepath,
-        source_code,
-        err,
-    } in errs.into_iter()
-    {
-        for (i, line) in LinesWithEndings::from(&source_code).enumerate() {
-            if i < err.code_range.start.row || i > err.code_range.end.row {
-                continue;
-            }
-            let ranges: Vec<(Style, &str)> = h.highlight(line, &ps);
-            let escaped = as_24_bit_terminal_escaped(ranges.as_slice(), false);
-            println!(
-                "{}: {}:{} - {}:{}",
-                Green.paint(filepath.clone()),
-                Cyan.paint(err.code_range.start.row.to_string()),
-                Cyan.paint(err.code_range.start.column.to_string()),
-                Cyan.paint(err.code_range.end.row.to_string()),
-                Cyan.paint(err.code_range.end.column.to_string()),
-            );
-            println!("{} {}", Red.paint("ERROR:"), Red.paint(err.rule.desc));
-            println!("{} | {}", format!("{:>6}", i), escaped);
-
-            if let Some(hint) = err.rule.hint {
-                for hint_line in hint.trim_start().lines() {
-                    let ranges: Vec<(Style, &str)> = h.highlight(hint_line, &ps);
-                    let escaped = as_24_bit_terminal_escaped(ranges.as_slice(), false);
-                    println!("{}", escaped);
-                }
-            }
-            println!();
-        }
-    }
-    Ok(())
+    // Apply the machine applicable fixes to the files.
+    #[structopt(long)]
+    fix: bool,
+
+    // Print the fixes as a unified diff instead of applying them.
+    #[structopt(long, requires = "fix")]
+    dry_run: bool,
+
+    // `json` prints a single document and `ndjson` prints one finding per line.
+    // `cargo` prints the messages of `cargo --message-format=json`.
+    #[structopt(
+        long,
+        default_value = "text",
+        possible_values = &["text", "json", "ndjson", "sarif", "junit", "checkstyle", "github", "gitlab", "cargo"]
+    )]
+    format: Format,
+
+    // `auto` colors the output of a terminal unless `NO_COLOR` is set.
+    #[structopt(long, default_value = "auto", possible_values = &["auto", "always", "never"])]
+    color: ColorChoice,
+
+    // The name of a syntect theme or the path of a `.tmTheme` file.
+    #[structopt(long)]
+    theme: Option<String>,
+
+    // Don't highlight the source code and the hints.
+    #[structopt(long)]
+    plain: bool,
+
+    // The number of lines to show before and after each finding, `context` in the config.
+    #[structopt(long)]
+    context: Option<usize>,
+
+    // Only print the summary without the findings.
+    #[structopt(long)]
+    summary_only: bool,
+
+    // The number of files and directories with the most findings in the summary.
+    #[structopt(long, default_value = "10")]
+    top: usize,
+
+    // `-q` only prints the findings and `-qq` prints nothing.
+    #[structopt(short, long, parse(from_occurrences), conflicts_with = "verbose")]
+    quiet: u8,
+
+    // Also print the linted and the skipped files and the time spent on each file.
+    #[structopt(short, long)]
+    verbose: bool,
+
+    // Pass the run as long as the findings don't exceed the limits.
+    // Without any limit, any finding fails the run.
+    #[structopt(long)]
+    max_findings: Option<usize>,
+
+    #[structopt(long)]
+    max_warnings: Option<usize>,
+
+    #[structopt(long)]
+    max_errors: Option<usize>,
+
+    // Fail the run when the findings of a rule in a directory exceed the ratchet file.
+    #[structopt(long)]
+    ratchet: Option<String>,
+
+    // Lower the counts of the ratchet file, `mylint-ratchet.toml` by default.
+    // It needs a run over the whole tree, without paths, `--include` or `--exclude`.
+    #[structopt(long, conflicts_with = "stdin")]
+    update_ratchet: bool,
+
+    // Lint the source code from stdin, like an unsaved buffer of an editor.
+    #[structopt(long)]
+    stdin: bool,
+
+    // The path that the source code from stdin is linted as, `stdin.rs` by default.
+    #[structopt(long, requires = "stdin")]
+    stdin_filename: Option<String>,
+
+    // Defaults to `mylint.toml` in the current directory if it exists.
+    #[structopt(long)]
+    config: Option<String>,
 }
 
 fn print_rules() {
@@ -92,78 +160,621 @@ fn print_rules() {
 
 fn print_help() {
     println!("{}", Green.paint("Run linter:"));
-    println!("\t{}", Green.paint("mylint -p <directoy>"));
+    println!("\t{}", Green.paint("mylint <files or directories>..."));
+    println!(
+        "{}",
+        Green.paint("Only lint the files matching the globs or skip them:")
+    );
+    println!(
+        "\t{}",
+        Green.paint("mylint --include <glob> --exclude <glob>")
+    );
+    println!(
+        "{}",
+        Green.paint("Lint the files ignored by .gitignore or .ignore:")
+    );
+    println!("\t{}", Green.paint("mylint --no-ignore"));
+    println!(
+        "{}",
+        Green.paint("Lint every file without the cache in target/mylint:")
+    );
+    println!("\t{}", Green.paint("mylint --no-cache"));
+    println!("{}", Green.paint("Lint the generated files too:"));
+    println!("\t{}", Green.paint("mylint --lint-generated"));
     println!("{}", Green.paint("List all rules:"));
     println!("\t{}", Green.paint("mylint -l"));
     println!("{}", Green.paint("Suppress rules:"));
     println!("\t{}", Green.paint("mylint -s <rules>"));
+    println!(
+        "{}",
+        Green.paint("Only report expect calls on known Option or Result:")
+    );
+    println!("\t{}", Green.paint("mylint --expect-receivers known"));
+    println!("{}", Green.paint("Apply the machine applicable fixes:"));
+    println!("\t{}", Green.paint("mylint --fix"));
+    println!(
+        "{}",
+        Green.paint("Show the fixes as a diff without applying them:")
+    );
+    println!("\t{}", Green.paint("mylint --fix --dry-run"));
+    println!("{}", Green.paint("Print the findings as JSON:"));
+    println!("\t{}", Green.paint("mylint --format json"));
+    println!("{}", Green.paint("Print the findings as a SARIF log:"));
+    println!("\t{}", Green.paint("mylint --format sarif"));
+    println!(
+        "{}",
+        Green.paint("Print the findings as JUnit or Checkstyle XML:")
+    );
+    println!("\t{}", Green.paint("mylint --format junit"));
+    println!("\t{}", Green.paint("mylint --format checkstyle"));
+    println!(
+        "{}",
+        Green.paint("Annotate the findings in GitHub Actions or GitLab CI:")
+    );
+    println!("\t{}", Green.paint("mylint --format github"));
+    println!("\t{}", Green.paint("mylint --format gitlab"));
+    println!(
+        "{}",
+        Green.paint("Print the findings as cargo JSON messages for the editors:")
+    );
+    println!("\t{}", Green.paint("mylint --format cargo"));
+    println!("{}", Green.paint("Choose when to color the output:"));
+    println!("\t{}", Green.paint("mylint --color auto|always|never"));
+    println!(
+        "{}",
+        Green.paint("Highlight the source code with another theme:")
+    );
+    println!(
+        "\t{}",
+        Green.paint("mylint --theme <name or .tmTheme file>")
+    );
+    println!("{}", Green.paint("Don't highlight the source code:"));
+    println!("\t{}", Green.paint("mylint --plain"));
+    println!("{}", Green.paint("Show the lines around the findings:"));
+    println!("\t{}", Green.paint("mylint --context <lines>"));
+    println!("{}", Green.paint("Only print the summary of the findings:"));
+    println!("\t{}", Green.paint("mylint --summary-only --top <count>"));
+    println!(
+        "{}",
+        Green.paint("Only print the findings, or nothing but the exit code:")
+    );
+    println!("\t{}", Green.paint("mylint -q"));
+    println!("\t{}", Green.paint("mylint -qq"));
+    println!(
+        "{}",
+        Green.paint("Also print the linted files and the timing:")
+    );
+    println!("\t{}", Green.paint("mylint -v"));
+    println!(
+        "{}",
+        Green.paint("Pass the run while the findings are within the limits:")
+    );
+    println!("\t{}", Green.paint("mylint --max-findings <count>"));
+    println!(
+        "\t{}",
+        Green.paint("mylint --max-warnings <count> --max-errors <count>")
+    );
+    println!(
+        "{}",
+        Green.paint("Fail when the findings of a directory go up and lower the counts:")
+    );
+    println!(
+        "\t{}",
+        Green.paint("mylint --ratchet <mylint-ratchet.toml>")
+    );
+    println!("\t{}", Green.paint("mylint --update-ratchet"));
+    println!(
+        "{}",
+        Green.paint("Lint the source code from stdin as the given file:")
+    );
+    println!(
+        "\t{}",
+        Green.paint("mylint --stdin --stdin-filename <path/to/file.rs>")
+    );
+    println!("{}", Green.paint("Read the config from another file:"));
+    println!("\t{}", Green.paint("mylint --config <mylint.toml>"));
 }
 
-fn main() -> Result<(), String> {
+// Everything but the findings goes to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Verbosity {
+    Silent,
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl Verbosity {
+    fn from_opt(opt: &Opt) -> Self {
+        match (opt.quiet, opt.verbose) {
+            (0, true) => Verbosity::Verbose,
+            (0, false) => Verbosity::Normal,
+            (1, _) => Verbosity::Quiet,
+            _ => Verbosity::Silent,
+        }
+    }
+}
+
+enum Outcome {
+    Clean,
+    Findings,
+}
+
+struct Limits {
+    findings: Option<usize>,
+    warnings: Option<usize>,
+    errors: Option<usize>,
+    // The ratchet replaces the default limit of no findings.
+    ratchet: bool,
+}
+
+impl Limits {
+    // The reasons why the run fails.
+    // The errors are certain panics, so they're not allowed unless `--max-errors` is given.
+    fn exceeded(&self, summary: &Summary) -> Vec<String> {
+        let findings = match (self.findings, self.warnings, self.errors) {
+            (None, None, None) if !self.ratchet => Some(0),
+            (findings, _, _) => findings,
+        };
+        let counts = [
+            ("findings", findings, summary.findings()),
+            ("warnings", self.warnings, summary.count(Severity::Warning)),
+            (
+                "errors",
+                Some(self.errors.unwrap_or(0)),
+                summary.count(Severity::Error),
+            ),
+        ];
+        counts
+            .iter()
+            .filter_map(|&(name, limit, count)| match limit {
+                Some(limit) if count > limit => Some(if limit == 0 {
+                    format!("{} {}", count, name)
+                } else {
+                    format!("{} {} exceed the limit of {}", count, name, limit)
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+// The options which lint only a part of the tree, and so would lower the ratchet
+// of the files they leave out.
+fn partial_run(opt: &Opt) -> Option<&'static str> {
+    if !opt.paths.is_empty() || opt.path.is_some() {
+        Some("paths")
+    } else if !opt.include.is_empty() {
+        Some("--include")
+    } else if !opt.exclude.is_empty() {
+        Some("--exclude")
+    } else if opt.stdin {
+        Some("--stdin")
+    } else {
+        None
+    }
+}
+
+fn main() {
+    let opt = match Opt::from_iter_safe(env::args_os()) {
+        Ok(opt) => opt,
+        // The help and the version are not errors.
+        Err(err) if !err.use_stderr() => err.exit(),
+        Err(err) => {
+            eprintln!("{}", err.message);
+            process::exit(EXIT_ERROR);
+        }
+    };
+    let code = match run(opt) {
+        Ok(Outcome::Clean) => EXIT_CLEAN,
+        Ok(Outcome::Findings) => EXIT_FINDINGS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            EXIT_ERROR
+        }
+    };
+    process::exit(code);
+}
+
+fn run(opt: Opt) -> Result<Outcome, String> {
+    let started = Instant::now();
     env_logger::init();
-    let opt = Opt::from_args();
 
     if opt.help {
         print_help();
-        return Ok(());
+        return Ok(Outcome::Clean);
     }
 
     if opt.list {
         print_rules();
-        return Ok(());
+        return Ok(Outcome::Clean);
+    }
+
+    let file_config = Config::load(opt.config.as_deref())?;
+    if opt.update_ratchet {
+        if let Some(option) = partial_run(&opt) {
+            return Err(format!(
+                "--update-ratchet only works on the whole tree, not with {}",
+                option
+            ));
+        }
     }
 
+    let verbosity = Verbosity::from_opt(&opt);
+    let verbose = verbosity >= Verbosity::Verbose;
+    let color = opt.color.use_color();
     let suppress = opt.suppress.clone();
-    if !suppress.is_empty() {
-        println!("{}", Red.paint(format!("Suppressing: {:#?}", suppress)));
+    if verbose && !suppress.is_empty() {
+        let message = format!("Suppressing: {:?}", suppress);
+        if color {
+            eprintln!("{}", Red.paint(message));
+        } else {
+            eprintln!("{}", message);
+        }
+    }
+
+    let include: Vec<String> = file_config
+        .include
+        .iter()
+        .chain(&opt.include)
+        .cloned()
+        .collect();
+    let exclude: Vec<String> = file_config
+        .exclude
+        .iter()
+        .chain(&opt.exclude)
+        .cloned()
+        .collect();
+    let mut selector = FileSelector::new(&include, &exclude)?;
+    if !opt.lint_generated {
+        selector = selector.with_generated(&file_config.generated)?;
     }
 
+    // The source code from stdin is linted as if it were at `--stdin-filename`.
+    let mut stdin_source = None;
     let mut paths = vec![];
-    ScanDir::files()
-        .walk(opt.path.clone(), |iter| {
-            for (entry, name) in iter {
-                if !name.ends_with(".rs") {
-                    continue;
-                }
-                paths.push(entry.path());
+    let mut skipped = vec![];
+    let mut walk_failures = 0;
+    if opt.stdin {
+        if opt.fix && !opt.dry_run {
+            return Err("--fix can't write to stdin, use --fix --dry-run".to_string());
+        }
+        let mut source_code = String::new();
+        io::stdin()
+            .read_to_string(&mut source_code)
+            .map_err(|err| format!("failed to read stdin: {}", err))?;
+        stdin_source = Some(source_code);
+        let path = PathBuf::from(opt.stdin_filename.as_deref().unwrap_or("stdin.rs"));
+        match selector.skip_reason(&path) {
+            Some(reason) => skipped.push((path, reason)),
+            None => paths.push(path),
+        }
+    } else {
+        let mut roots: Vec<String> = opt.paths.iter().chain(&opt.path).cloned().collect();
+        if roots.is_empty() {
+            roots.push("./src".to_string());
+        }
+        for root in &roots {
+            if !Path::new(root).exists() {
+                return Err(format!("failed to open path {}: not found", root));
+            }
+        }
+        let files = find_files(&roots, opt.no_ignore, &selector);
+        if verbosity >= Verbosity::Quiet {
+            for err in &files.errors {
+                eprintln!("failed to find files: {}", err);
             }
-        })
-        .map_err(|err| format!("failed to open path {}: {:#?}", opt.path.as_str(), err))?;
+        }
+        walk_failures = files.errors.len();
+        paths = files.paths;
+        skipped = files.skipped;
+    }
 
-    if paths.is_empty() {
+    if verbose {
+        for (path, reason) in &skipped {
+            eprintln!("skipped {}: {}", path.to_string_lossy(), reason);
+        }
+    }
+    if paths.is_empty() && skipped.is_empty() && walk_failures == 0 {
         return Err("failed to find any files".to_string());
     }
 
-    for p in &paths {
-        println!("{}", p.to_string_lossy());
-    }
+    let config = LintConfig {
+        expect_receivers: opt.expect_receivers,
+    };
+    // Everything that changes the findings besides the source code and the mylint version.
+    let mut suppressed: Vec<String> = suppress.iter().map(|s| s.to_lowercase()).collect();
+    suppressed.sort();
+    let cache_config = format!("{:?}\0{:?}", suppressed, config);
+    // The source code from stdin is usually an unsaved buffer which is linted only once,
+    // and it would overwrite the cached findings of the file on disk.
+    let cache = if opt.no_cache || opt.stdin {
+        None
+    } else {
+        match Cache::open(Path::new(CACHE_DIR), &cache_config) {
+            Ok(cache) => Some(cache),
+            Err(err) => {
+                if verbose {
+                    eprintln!("warning: linting without the cache: {}", err);
+                }
+                None
+            }
+        }
+    };
+    let validator = AllRulesValidator::new(suppress, default_filter(), &config);
+    let text_options = TextOptions {
+        color,
+        theme: opt.theme.clone(),
+        plain: opt.plain,
+        context: opt.context.or(file_config.context).unwrap_or(0),
+    };
+    let mut reporter = get_reporter(opt.format, &text_options)?;
+    let mut out = io::stdout();
+    let mut summary = Summary::new(started);
+    summary.skipped = skipped.len();
+    // The paths that can't be walked fail the run like the files that can't be read.
+    summary.failed = walk_failures;
+    let mut current = Ratchet::default();
+    let mut pending_fixes = 0;
 
-    let validator = AllRulesValidator::new(suppress, default_filter());
-    let mut errs = vec![];
+    // `-qq` and `--summary-only` don't print the findings.
+    let report = verbosity >= Verbosity::Quiet && !opt.summary_only;
 
     for path in paths.into_iter() {
+        let file_started = Instant::now();
         let path_str = path.to_string_lossy().to_owned();
-        let source_code = fs::read_to_string(path.clone())
-            .map_err(|err| format!("failed to open file {}: {}", path_str, err))?;
+        // A file that can't be read or parsed doesn't stop the others from being linted.
+        let source_code = match &stdin_source {
+            Some(source_code) => Ok(source_code.clone()),
+            None => fs::read_to_string(path.clone()),
+        };
+        let mut source_code = match source_code {
+            Ok(source_code) => source_code,
+            Err(err) => {
+                if verbosity >= Verbosity::Quiet {
+                    eprintln!("failed to open file {}: {}", path_str, err);
+                }
+                summary.failed += 1;
+                continue;
+            }
+        };
+        if !opt.lint_generated {
+            if let Some(reason) = generated_marker(&source_code) {
+                if verbose {
+                    eprintln!("skipped {}: {}", path_str, reason);
+                }
+                summary.skipped += 1;
+                continue;
+            }
+        }
 
-        let source = SourceCode::parse(&source_code)
-            .ok_or_else(|| format!("failed to parse source code: {}", path_str))?;
-        let root_node = source.get_root_node();
-        let res = validator.validate(&root_node, &source_code);
+        if opt.fix {
+            let fixed = match fix_source(&validator, &source_code) {
+                Ok(fixed) => fixed,
+                Err(err) => {
+                    if verbosity >= Verbosity::Quiet {
+                        eprintln!("failed to fix file {}: {}", path_str, err);
+                    }
+                    summary.failed += 1;
+                    continue;
+                }
+            };
+            if opt.dry_run {
+                print!("{}", unified_diff(&path_str, &source_code, &fixed.source));
+                pending_fixes += fixed.applied;
+                continue;
+            }
+            if fixed.applied > 0 {
+                if let Err(err) = fs::write(&path, &fixed.source) {
+                    if verbosity >= Verbosity::Quiet {
+                        eprintln!("failed to write file {}: {}", path_str, err);
+                    }
+                    summary.failed += 1;
+                    continue;
+                }
+                if verbosity >= Verbosity::Normal {
+                    eprintln!("Fixed {} findings in {}", fixed.applied, path_str);
+                }
+                source_code = fixed.source;
+            }
+        }
 
-        if let Err(err) = res {
-            errs.push(LintErr {
-                filepath: path_str.to_string(),
-                source_code: source_code.to_string(),
-                err,
-            });
+        let cached = cache
+            .as_ref()
+            .and_then(|cache| cache.get(&path_str, &source_code));
+        let from_cache = cached.is_some();
+        let errs = match cached {
+            Some(errs) => errs,
+            None => {
+                let source = match SourceCode::parse(&source_code) {
+                    Some(source) => source,
+                    None => {
+                        if verbosity >= Verbosity::Quiet {
+                            eprintln!("failed to parse source code: {}", path_str);
+                        }
+                        summary.failed += 1;
+                        continue;
+                    }
+                };
+                let root_node = source.get_root_node();
+                let errs = validator.validate_all(&root_node, &source_code);
+                if let Some(cache) = &cache {
+                    cache.put(&path_str, &source_code, &errs);
+                }
+                errs
+            }
+        };
+        summary.add_file(&path_str, &errs);
+        current.add_file(&path_str, &errs);
+        if verbose {
+            eprintln!(
+                "linted {} in {:.2}ms: {} findings{}",
+                path_str,
+                file_started.elapsed().as_secs_f64() * 1000.0,
+                errs.len(),
+                if from_cache { " (cached)" } else { "" }
+            );
+        }
+        if !report {
+            continue;
         }
+
+        let file = FileReport {
+            path: &path_str,
+            source: &source_code,
+            errs: &errs,
+        };
+        reporter
+            .report_file(&mut out, &file)
+            .map_err(|err| format!("failed to write the report: {}", err))?;
     }
 
-    if errs.is_empty() {
-        Ok(())
-    } else {
-        print_err_lines(errs)?;
-        Err("Not all lint passed".to_string())
+    if opt.dry_run {
+        if pending_fixes > 0 && verbosity >= Verbosity::Quiet {
+            eprintln!(
+                "{} fixes to apply, run `mylint --fix` to apply them",
+                pending_fixes
+            );
+        }
+        if summary.failed > 0 {
+            return Err(format!("failed to lint {} files", summary.failed));
+        }
+        if pending_fixes == 0 {
+            return Ok(Outcome::Clean);
+        }
+        return Ok(Outcome::Findings);
+    }
+
+    if report {
+        reporter
+            .finish(&mut out)
+            .map_err(|err| format!("failed to write the report: {}", err))?;
+    }
+    if verbosity >= Verbosity::Normal {
+        summary
+            .render(&mut io::stderr(), opt.top)
+            .map_err(|err| format!("failed to write the summary: {}", err))?;
+    }
+
+    if summary.failed > 0 {
+        return Err(format!("failed to lint {} files", summary.failed));
+    }
+    let ratchet_path = match opt.ratchet.clone().or_else(|| file_config.ratchet.clone()) {
+        Some(path) => Some(path),
+        None if opt.update_ratchet => Some(RATCHET_FILE.to_string()),
+        None => None,
+    };
+    let mut exceeded = vec![];
+    if let Some(path) = &ratchet_path {
+        // The first update records the current counts.
+        if opt.update_ratchet && !Path::new(path).exists() {
+            current.save(path)?;
+            if verbosity >= Verbosity::Normal {
+                eprintln!("Created the ratchet {}", path);
+            }
+        }
+        let allowed = Ratchet::load(path)?;
+        exceeded.extend(allowed.increases(&current));
+        if opt.update_ratchet {
+            let updated = allowed.tightened(&current);
+            if updated != allowed {
+                updated.save(path)?;
+                if verbosity >= Verbosity::Normal {
+                    eprintln!("Updated the ratchet {}", path);
+                }
+            }
+        }
+    }
+
+    let limits = Limits {
+        findings: opt.max_findings.or(file_config.max_findings),
+        warnings: opt.max_warnings.or(file_config.max_warnings),
+        errors: opt.max_errors.or(file_config.max_errors),
+        ratchet: ratchet_path.is_some(),
+    };
+    exceeded.extend(limits.exceeded(&summary));
+    if exceeded.is_empty() {
+        return Ok(Outcome::Clean);
+    }
+    if verbosity >= Verbosity::Quiet {
+        eprintln!("Not all lint passed: {}", exceeded.join(", "));
+    }
+    Ok(Outcome::Findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mylint::{assert_ok, assert_some};
+
+    fn summary(source_code: &str) -> Summary {
+        let source = assert_some!(SourceCode::parse(source_code));
+        let errs = AllRulesValidator::default().validate_all(&source.get_root_node(), source_code);
+        let mut summary = Summary::new(Instant::now());
+        summary.add_file("src/lib.rs", &errs);
+        summary
+    }
+
+    fn limits(findings: Option<usize>, warnings: Option<usize>, errors: Option<usize>) -> Limits {
+        Limits {
+            findings,
+            warnings,
+            errors,
+            ratchet: false,
+        }
+    }
+
+    #[test]
+    fn test_partial_run() {
+        let partial_run = |args: &[&str]| {
+            let opt = Opt::from_iter_safe(Some("mylint").iter().chain(args));
+            partial_run(&assert_ok!(opt))
+        };
+        assert_eq!(partial_run(&["--update-ratchet"]), None);
+        assert_eq!(
+            partial_run(&["--update-ratchet", "src/lint/core.rs"]),
+            Some("paths")
+        );
+        assert_eq!(
+            partial_run(&["--update-ratchet", "-p", "src"]),
+            Some("paths")
+        );
+        assert_eq!(
+            partial_run(&["--update-ratchet", "--include", "src/lint/**"]),
+            Some("--include")
+        );
+        assert_eq!(
+            partial_run(&["--update-ratchet", "--exclude", "src/lint/**"]),
+            Some("--exclude")
+        );
+        assert_eq!(partial_run(&["--stdin"]), Some("--stdin"));
+    }
+
+    #[test]
+    fn test_exceeded() {
+        let warnings = summary("fn f() { a.unwrap(); b.unwrap(); }");
+        assert_eq!(
+            limits(None, None, None).exceeded(&warnings),
+            vec!["2 findings".to_string()]
+        );
+        assert!(limits(None, Some(2), None).exceeded(&warnings).is_empty());
+        assert_eq!(
+            limits(Some(1), Some(2), None).exceeded(&warnings),
+            vec!["2 findings exceed the limit of 1".to_string()]
+        );
+
+        // `--max-warnings` alone doesn't allow the errors.
+        let errors = summary(r#"fn f() { "70000".parse::<u16>().unwrap(); }"#);
+        assert_eq!(
+            limits(None, Some(5), None).exceeded(&errors),
+            vec!["1 errors".to_string()]
+        );
+        assert!(limits(None, Some(5), Some(1)).exceeded(&errors).is_empty());
+        let ratchet = Limits {
+            ratchet: true,
+            ..limits(None, None, None)
+        };
+        assert!(ratchet.exceeded(&warnings).is_empty());
+        assert_eq!(ratchet.exceeded(&errors), vec!["1 errors".to_string()]);
     }
 }
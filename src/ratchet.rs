@@ -0,0 +1,162 @@
+// The ratchet file holds the allowed number of findings of each rule in each directory.
+// The counts can only go down: the run fails when one goes up,
+// and `--update-ratchet` lowers the counts that went down.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use crate::report::summary::directory;
+use crate::ValidationError;
+
+pub const RATCHET_FILE: &str = "mylint-ratchet.toml";
+
+// Directory -> rule code -> count.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Ratchet {
+    counts: BTreeMap<String, BTreeMap<String, usize>>,
+    // The directories with at least one linted file, they are not saved.
+    linted: BTreeSet<String>,
+}
+
+impl Ratchet {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let counts = toml::from_str(content).map_err(|err| format!("invalid ratchet: {}", err))?;
+        Ok(Self {
+            counts,
+            linted: BTreeSet::new(),
+        })
+    }
+
+    // A missing file allows no findings.
+    pub fn load(path: &str) -> Result<Self, String> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read ratchet {}: {}", path, err))?;
+        Self::parse(&content).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let content = toml::to_string(&self.counts)
+            .map_err(|err| format!("failed to serialize ratchet: {}", err))?;
+        fs::write(path, content).map_err(|err| format!("failed to write ratchet {}: {}", path, err))
+    }
+
+    pub fn add_file(&mut self, path: &str, errs: &[ValidationError]) {
+        let dir = directory(path);
+        for err in errs {
+            *self
+                .counts
+                .entry(dir.clone())
+                .or_default()
+                .entry(err.rule.code.to_string())
+                .or_insert(0) += 1;
+        }
+        self.linted.insert(dir);
+    }
+
+    fn get(&self, dir: &str, code: &str) -> usize {
+        self.counts
+            .get(dir)
+            .and_then(|codes| codes.get(code))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    // The counts of `current` that went up.
+    pub fn increases(&self, current: &Ratchet) -> Vec<String> {
+        let mut increases = vec![];
+        for (dir, codes) in &current.counts {
+            for (code, &count) in codes {
+                let allowed = self.get(dir, code);
+                if count > allowed {
+                    increases.push(format!(
+                        "{} {} findings in {} exceed the ratchet of {}",
+                        count, code, dir, allowed
+                    ));
+                }
+            }
+        }
+        increases
+    }
+
+    // Lower the counts to the ones of `current` without raising any of them.
+    // The directories that `current` didn't lint keep their counts.
+    pub fn tightened(&self, current: &Ratchet) -> Ratchet {
+        let mut counts: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+        for (dir, codes) in &self.counts {
+            let linted = current.linted.contains(dir);
+            for (code, &allowed) in codes {
+                let count = if linted {
+                    current.get(dir, code).min(allowed)
+                } else {
+                    allowed
+                };
+                if count > 0 {
+                    counts
+                        .entry(dir.clone())
+                        .or_default()
+                        .insert(code.clone(), count);
+                }
+            }
+        }
+        Ratchet {
+            counts,
+            linted: BTreeSet::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AllRulesValidator, SourceCode};
+
+    fn ratchet(files: &[(&str, &str)]) -> Ratchet {
+        let mut ratchet = Ratchet::default();
+        for (path, source_code) in files {
+            let source = assert_some!(SourceCode::parse(source_code));
+            let errs =
+                AllRulesValidator::default().validate_all(&source.get_root_node(), source_code);
+            ratchet.add_file(path, &errs);
+        }
+        ratchet
+    }
+
+    #[test]
+    fn test_ratchet() {
+        let allowed = assert_ok!(Ratchet::parse(
+            "[src]\nUnwrap = 2\nIndexExpression = 1\n\n[\"src/lint\"]\nUnwrap = 1\n"
+        ));
+        let current = ratchet(&[
+            ("./src/main.rs", "fn f() { a.unwrap(); }"),
+            ("src/lint/mod.rs", "fn f() { a.unwrap(); b.unwrap(); }"),
+        ]);
+        assert_eq!(
+            allowed.increases(&current),
+            vec!["2 Unwrap findings in src/lint exceed the ratchet of 1".to_string()]
+        );
+
+        // The fixed index expression is removed and the new unwrap call is not allowed.
+        let tightened = allowed.tightened(&current);
+        let expected = assert_ok!(Ratchet::parse(
+            "[src]\nUnwrap = 1\n\n[\"src/lint\"]\nUnwrap = 1\n"
+        ));
+        assert_eq!(tightened, expected);
+        let saved = assert_ok!(toml::to_string(&tightened.counts));
+        assert_eq!(assert_ok!(Ratchet::parse(&saved)), tightened);
+    }
+
+    #[test]
+    fn test_partial_run() {
+        let allowed = assert_ok!(Ratchet::parse(
+            "[\"src/a\"]\nUnwrap = 1\n\n[\"src/b\"]\nUnwrap = 1\n"
+        ));
+        // Only `src/a` is linted, and the unwrap call in it is fixed.
+        let current = ratchet(&[("./src/a/lib.rs", "fn f() {}")]);
+        assert!(allowed.increases(&current).is_empty());
+        let expected = assert_ok!(Ratchet::parse("[\"src/b\"]\nUnwrap = 1\n"));
+        assert_eq!(allowed.tightened(&current), expected);
+    }
+}
@@ -0,0 +1,199 @@
+// The `compiler-message` records of `cargo --message-format=json`,
+// so that the editors can show the findings next to the ones of rustc.
+use serde_json::{json, Value};
+use std::env;
+use std::io::{self, Write};
+
+use super::text::render_plain;
+use super::{column, line_number, records, relative_path, FileReport, Record, Reporter};
+use crate::Severity;
+
+pub struct CargoReporter {
+    package_id: String,
+    manifest_path: String,
+    name: String,
+}
+
+impl CargoReporter {
+    // The package is the one in the current directory, which is where cargo would run.
+    pub fn new() -> Self {
+        let dir = env::current_dir().unwrap_or_default();
+        let name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "mylint".to_string());
+        Self {
+            package_id: format!("path+file://{}", dir.to_string_lossy()),
+            manifest_path: dir.join("Cargo.toml").to_string_lossy().to_string(),
+            name,
+        }
+    }
+}
+
+impl Default for CargoReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reporter for CargoReporter {
+    fn report_file(&mut self, out: &mut dyn Write, file: &FileReport) -> io::Result<()> {
+        // The records are in the order of the errors.
+        for (record, err) in records(file).iter().zip(file.errs) {
+            let message = json!({
+                "reason": "compiler-message",
+                "package_id": self.package_id,
+                "manifest_path": self.manifest_path,
+                "target": {
+                    "kind": ["lib"],
+                    "crate_types": ["lib"],
+                    "name": self.name,
+                    "src_path": relative_path(file.path),
+                    "edition": "2018",
+                    "doctest": false,
+                    "test": false,
+                },
+                "message": diagnostic(file.source, record, render_plain(file, err)?),
+            });
+            serde_json::to_writer(&mut *out, &message)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+fn diagnostic(source: &str, record: &Record, rendered: String) -> Value {
+    let level = match record.severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    };
+    let code = record.code.to_string();
+    let mut children = vec![];
+    if let Some(note) = record.note {
+        children.push(child("note", note, vec![]));
+    }
+    if let Some(fix) = &record.fix {
+        let applicability = if fix.machine_applicable {
+            "MachineApplicable"
+        } else {
+            "MaybeIncorrect"
+        };
+        let spans = fix
+            .edits
+            .iter()
+            .map(|edit| {
+                let mut span = span(source, record.file, edit.start_byte, edit.end_byte);
+                span["suggested_replacement"] = json!(edit.replacement);
+                span["suggestion_applicability"] = json!(applicability);
+                span
+            })
+            .collect();
+        children.push(child("help", fix.message, spans));
+    }
+    if let Some(hint) = record.hint {
+        children.push(child("help", hint, vec![]));
+    }
+    json!({
+        "$message_type": "diagnostic",
+        "message": record.message,
+        "code": { "code": code, "explanation": null },
+        "level": level,
+        "spans": [span(source, record.file, record.start_byte, record.end_byte)],
+        "children": children,
+        "rendered": rendered,
+    })
+}
+
+fn child(level: &str, message: &str, spans: Vec<Value>) -> Value {
+    json!({
+        "message": message,
+        "code": null,
+        "level": level,
+        "spans": spans,
+        "children": [],
+        "rendered": null,
+    })
+}
+
+// The lines and columns are 1-based and the columns count characters like rustc.
+fn span(source: &str, file: &str, start: usize, end: usize) -> Value {
+    let line_start = line_number(source, start);
+    let line_end = line_number(source, end);
+    let column_start = column(source, start);
+    let column_end = column(source, end);
+    let text: Vec<Value> = source
+        .lines()
+        .enumerate()
+        .skip(line_start - 1)
+        .take(line_end - line_start + 1)
+        .map(|(i, text)| {
+            let highlight_start = if i + 1 == line_start { column_start } else { 1 };
+            let highlight_end = if i + 1 == line_end {
+                column_end
+            } else {
+                text.chars().count() + 1
+            };
+            json!({
+                "text": text,
+                "highlight_start": highlight_start,
+                "highlight_end": highlight_end,
+            })
+        })
+        .collect();
+    json!({
+        "file_name": file,
+        "byte_start": start,
+        "byte_end": end,
+        "line_start": line_start,
+        "line_end": line_end,
+        "column_start": column_start,
+        "column_end": column_end,
+        "is_primary": true,
+        "text": text,
+        "label": null,
+        "suggested_replacement": null,
+        "suggestion_applicability": null,
+        "expansion": null,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::tests::{report, report_at};
+
+    #[test]
+    fn test_cargo() {
+        let source = "fn f(a: Option<u8>) -> Option<u8> {\n    Some(a.unwrap())\n}";
+        let output = report(&mut CargoReporter::new(), source);
+        let message: Value = assert_ok!(serde_json::from_str(output.trim_end()));
+        assert_eq!(message["reason"], "compiler-message");
+        assert_eq!(message["target"]["src_path"], "src/lib.rs");
+        let diagnostic = &message["message"];
+        assert_eq!(diagnostic["code"]["code"], "Unwrap");
+        assert_eq!(diagnostic["level"], "warning");
+        let span = &diagnostic["spans"][0];
+        assert_eq!(
+            (&span["line_start"], &span["column_start"]),
+            (&json!(2), &json!(12))
+        );
+        assert_eq!(span["text"][0]["text"], "    Some(a.unwrap())");
+        assert_eq!(span["text"][0]["highlight_end"], 18);
+        let help = &diagnostic["children"][0];
+        assert_eq!(help["level"], "help");
+        let suggestion = &help["spans"][0];
+        assert_eq!(suggestion["suggested_replacement"], "a?");
+        assert_eq!(suggestion["suggestion_applicability"], "MachineApplicable");
+        assert_eq!(suggestion["column_start"], 10);
+        let rendered = assert_some!(diagnostic["rendered"].as_str());
+        assert!(
+            rendered.starts_with("warning[Unwrap]: Unwrap call may panic.\n --> src/lib.rs:2:12\n")
+        );
+        assert!(rendered.contains("2 |     Some(a.unwrap())\n  |            ^^^^^^\n"));
+        assert!(!rendered.contains('\x1b'));
+        assert_eq!(
+            report_at(&mut CargoReporter::new(), "./src/lib.rs", source),
+            output
+        );
+    }
+}
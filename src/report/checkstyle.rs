@@ -0,0 +1,67 @@
+// Checkstyle XML for the static analysis widgets of the CI servers.
+use std::io::{self, Write};
+
+use super::{escape_xml, records, relative_path, FileReport, Reporter};
+
+#[derive(Default)]
+pub struct CheckstyleReporter {
+    files: Vec<String>,
+}
+
+impl Reporter for CheckstyleReporter {
+    fn report_file(&mut self, _out: &mut dyn Write, file: &FileReport) -> io::Result<()> {
+        let mut element = format!(
+            "  <file name=\"{}\">\n",
+            escape_xml(relative_path(file.path))
+        );
+        for record in records(file) {
+            element.push_str(&format!(
+                "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"{}\"/>\n",
+                record.start_line,
+                record.start_column,
+                record.severity,
+                escape_xml(record.message),
+                record.code.to_string()
+            ));
+        }
+        element.push_str("  </file>\n");
+        self.files.push(element);
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(out, "<checkstyle version=\"4.3\">")?;
+        for file in &self.files {
+            write!(out, "{}", file)?;
+        }
+        writeln!(out, "</checkstyle>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::tests::{report, report_at};
+
+    #[test]
+    fn test_checkstyle() {
+        let output = report(&mut CheckstyleReporter::default(), "fn f() { a[0]; }");
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="src/lib.rs">
+    <error line="1" column="10" severity="warning" message="index operation may panic, use get method instead." source="IndexExpression"/>
+  </file>
+</checkstyle>
+"#;
+        assert_eq!(output, expected);
+        assert_eq!(
+            report_at(
+                &mut CheckstyleReporter::default(),
+                "./src/lib.rs",
+                "fn f() { a[0]; }"
+            ),
+            expected
+        );
+    }
+}
@@ -0,0 +1,66 @@
+// GitHub Actions workflow commands, which show the findings inline on the pull requests.
+use std::io::{self, Write};
+
+use super::{records, FileReport, Reporter};
+use crate::Severity;
+
+pub struct GithubReporter;
+
+impl Reporter for GithubReporter {
+    fn report_file(&mut self, out: &mut dyn Write, file: &FileReport) -> io::Result<()> {
+        for record in records(file) {
+            let command = match record.severity {
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            };
+            let message = match record.note {
+                Some(note) => format!("{} ({})", record.message, note),
+                None => record.message.to_string(),
+            };
+            writeln!(
+                out,
+                "::{} file={},line={},col={},endLine={},endColumn={},title={}::{}",
+                command,
+                escape_property(record.file),
+                record.start_line,
+                record.start_column,
+                record.end_line,
+                record.end_column,
+                escape_property(&format!("mylint {}", record.code.to_string())),
+                escape_data(&message)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::tests::{report, report_at};
+
+    #[test]
+    fn test_github() {
+        let source = "fn f() { a.unwrap(); }";
+        let output = report(&mut GithubReporter, source);
+        assert_eq!(
+            output,
+            "::warning file=src/lib.rs,line=1,col=12,endLine=1,endColumn=18,title=mylint Unwrap::Unwrap call may panic.\n"
+        );
+        assert_eq!(
+            report_at(&mut GithubReporter, "./src/lib.rs", source),
+            output
+        );
+        assert_eq!(escape_property("a,b:c%\n"), "a%2Cb%3Ac%25%0A");
+    }
+}
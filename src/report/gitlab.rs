@@ -0,0 +1,68 @@
+// GitLab Code Quality report, which shows the findings in the merge request widget.
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+use super::{records, FileReport, Reporter};
+use crate::Severity;
+
+#[derive(Default)]
+pub struct GitlabReporter {
+    issues: Vec<Value>,
+}
+
+impl Reporter for GitlabReporter {
+    fn report_file(&mut self, _out: &mut dyn Write, file: &FileReport) -> io::Result<()> {
+        for record in records(file) {
+            let severity = match record.severity {
+                Severity::Warning => "minor",
+                Severity::Error => "major",
+            };
+            let description = match record.note {
+                Some(note) => format!("{} ({})", record.message, note),
+                None => record.message.to_string(),
+            };
+            self.issues.push(json!({
+                "type": "issue",
+                "description": description,
+                "check_name": record.code.to_string(),
+                "categories": ["Bug Risk"],
+                "fingerprint": record.fingerprint,
+                "severity": severity,
+                "location": {
+                    "path": record.file,
+                    "lines": { "begin": record.start_line, "end": record.end_line },
+                },
+            }));
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *out, &self.issues)?;
+        writeln!(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::tests::{report, report_at};
+
+    #[test]
+    fn test_gitlab() {
+        let source = "fn f() {\n    a.unwrap();\n}";
+        let output = report(&mut GitlabReporter::default(), source);
+        let issues: Value = assert_ok!(serde_json::from_str(&output));
+        let issue = &issues[0];
+        assert_eq!(issue["check_name"], "Unwrap");
+        assert_eq!(issue["description"], "Unwrap call may panic.");
+        assert_eq!(issue["severity"], "minor");
+        assert_eq!(issue["location"]["path"], "src/lib.rs");
+        assert_eq!(issue["location"]["lines"]["begin"], 2);
+        assert_eq!(issue["fingerprint"].as_str().map(str::len), Some(16));
+
+        // The same path and fingerprint without the `./` prefix.
+        let prefixed = report_at(&mut GitlabReporter::default(), "./src/lib.rs", source);
+        assert_eq!(prefixed, output);
+    }
+}
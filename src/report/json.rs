@@ -0,0 +1,93 @@
+// The JSON output whose schema is documented in the README.
+// Bump the version on any incompatible change of the records.
+use serde::Serialize;
+use std::io::{self, Write};
+
+use super::{records, FileReport, Record, Reporter};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Document<'a> {
+    version: u32,
+    findings: &'a [serde_json::Value],
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    version: u32,
+    #[serde(flatten)]
+    record: &'a Record<'a>,
+}
+
+// Prints a single JSON document after all the files are linted.
+#[derive(Default)]
+pub struct JsonReporter {
+    findings: Vec<serde_json::Value>,
+}
+
+impl Reporter for JsonReporter {
+    fn report_file(&mut self, _out: &mut dyn Write, file: &FileReport) -> io::Result<()> {
+        for record in records(file) {
+            self.findings.push(serde_json::to_value(&record)?);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        let document = Document {
+            version: SCHEMA_VERSION,
+            findings: &self.findings,
+        };
+        serde_json::to_writer_pretty(&mut *out, &document)?;
+        writeln!(out)
+    }
+}
+
+// Prints one record per line as soon as a file is linted.
+pub struct NdjsonReporter;
+
+impl Reporter for NdjsonReporter {
+    fn report_file(&mut self, out: &mut dyn Write, file: &FileReport) -> io::Result<()> {
+        for record in records(file) {
+            let line = Line {
+                version: SCHEMA_VERSION,
+                record: &record,
+            };
+            serde_json::to_writer(&mut *out, &line)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::tests::report;
+
+    #[test]
+    fn test_json() {
+        let output = report(&mut JsonReporter::default(), "fn f() { a.unwrap(); }");
+        let value: serde_json::Value = assert_ok!(serde_json::from_str(&output));
+        assert_eq!(value["version"], 1);
+        let finding = &value["findings"][0];
+        assert_eq!(finding["code"], "Unwrap");
+        assert_eq!(finding["severity"], "warning");
+        assert_eq!(finding["file"], "src/lib.rs");
+        assert_eq!(finding["start_line"], 1);
+        assert_eq!(finding["start_column"], 12);
+        assert_eq!(finding["start_byte"], 11);
+        assert_eq!(finding["snippet"], "unwrap");
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let output = report(&mut NdjsonReporter, "fn f() { a.unwrap(); b.unwrap(); }");
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let value: serde_json::Value = assert_ok!(serde_json::from_str(lines[1]));
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["start_column"], 24);
+    }
+}
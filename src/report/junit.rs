@@ -0,0 +1,97 @@
+// JUnit XML for the test report widgets of the CI servers.
+// Every file is a test suite and every finding is a failed test case.
+use std::io::{self, Write};
+
+use super::{escape_xml, records, relative_path, FileReport, Reporter};
+
+#[derive(Default)]
+pub struct JunitReporter {
+    suites: Vec<String>,
+    tests: usize,
+    failures: usize,
+}
+
+impl Reporter for JunitReporter {
+    fn report_file(&mut self, _out: &mut dyn Write, file: &FileReport) -> io::Result<()> {
+        let path = escape_xml(relative_path(file.path));
+        let records = records(file);
+        let mut cases = String::new();
+        for record in &records {
+            let location = format!(
+                "{}:{}:{}",
+                record.file, record.start_line, record.start_column
+            );
+            cases.push_str(&format!(
+                "    <testcase name=\"{} at {}\" classname=\"{}\">\n",
+                record.code.to_string(),
+                escape_xml(&location),
+                path
+            ));
+            cases.push_str(&format!(
+                "      <failure message=\"{}\" type=\"{}\">{}: {}</failure>\n",
+                escape_xml(record.message),
+                record.code.to_string(),
+                escape_xml(&location),
+                escape_xml(record.snippet)
+            ));
+            cases.push_str("    </testcase>\n");
+        }
+        // A clean file is a single passed test case.
+        let tests = if records.is_empty() {
+            cases.push_str(&format!(
+                "    <testcase name=\"mylint\" classname=\"{}\"/>\n",
+                path
+            ));
+            1
+        } else {
+            records.len()
+        };
+        self.suites.push(format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\">\n{}  </testsuite>\n",
+            path,
+            tests,
+            records.len(),
+            cases
+        ));
+        self.tests += tests;
+        self.failures += records.len();
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            out,
+            "<testsuites name=\"mylint\" tests=\"{}\" failures=\"{}\" errors=\"0\">",
+            self.tests, self.failures
+        )?;
+        for suite in &self.suites {
+            write!(out, "{}", suite)?;
+        }
+        writeln!(out, "</testsuites>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::tests::{report, report_at};
+
+    #[test]
+    fn test_junit() {
+        let output = report(&mut JunitReporter::default(), "fn f() { a.unwrap(); }");
+        assert!(output.contains(r#"<testsuites name="mylint" tests="1" failures="1" errors="0">"#));
+        assert!(output.contains(r#"<testsuite name="src/lib.rs" tests="1" failures="1""#));
+        assert!(output
+            .contains(r#"<testcase name="Unwrap at src/lib.rs:1:12" classname="src/lib.rs">"#));
+        assert!(output.contains(r#"<failure message="Unwrap call may panic." type="Unwrap">"#));
+
+        let output = report(&mut JunitReporter::default(), "fn f() {}");
+        assert!(output.contains(r#"<testsuite name="src/lib.rs" tests="1" failures="0""#));
+        assert!(output.contains(r#"<testcase name="mylint" classname="src/lib.rs"/>"#));
+        assert_eq!(
+            report_at(&mut JunitReporter::default(), "./src/lib.rs", "fn f() {}"),
+            output
+        );
+    }
+}
@@ -0,0 +1,323 @@
+// The reporters print the validation errors of the linted files in different formats.
+use serde::{Serialize, Serializer};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::{Applicability, Fix, RuleCode, Severity, ValidationError};
+
+pub mod cargo;
+pub mod checkstyle;
+pub mod github;
+pub mod gitlab;
+pub mod json;
+pub mod junit;
+pub mod sarif;
+pub mod summary;
+pub mod text;
+
+pub use self::summary::Summary;
+pub use self::text::{ColorChoice, TextOptions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Ndjson,
+    Sarif,
+    Junit,
+    Checkstyle,
+    Github,
+    Gitlab,
+    Cargo,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            "sarif" => Ok(Format::Sarif),
+            "junit" => Ok(Format::Junit),
+            "checkstyle" => Ok(Format::Checkstyle),
+            "github" => Ok(Format::Github),
+            "gitlab" => Ok(Format::Gitlab),
+            "cargo" => Ok(Format::Cargo),
+            other => Err(format!("invalid format: {}", other)),
+        }
+    }
+}
+
+pub struct FileReport<'a> {
+    pub path: &'a str,
+    pub source: &'a str,
+    pub errs: &'a [ValidationError],
+}
+
+pub trait Reporter {
+    // Called once for every linted file, including the ones without any error.
+    fn report_file(&mut self, out: &mut dyn Write, file: &FileReport) -> io::Result<()>;
+
+    // Called after all the files are linted.
+    fn finish(&mut self, _out: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// The text options are ignored by the other formats.
+pub fn get_reporter(format: Format, options: &TextOptions) -> Result<Box<dyn Reporter>, String> {
+    let reporter: Box<dyn Reporter> = match format {
+        Format::Text => Box::new(text::TextReporter::new(options)?),
+        Format::Json => Box::new(json::JsonReporter::default()),
+        Format::Ndjson => Box::new(json::NdjsonReporter),
+        Format::Sarif => Box::new(sarif::SarifReporter::default()),
+        Format::Junit => Box::new(junit::JunitReporter::default()),
+        Format::Checkstyle => Box::new(checkstyle::CheckstyleReporter::default()),
+        Format::Github => Box::new(github::GithubReporter),
+        Format::Gitlab => Box::new(gitlab::GitlabReporter::default()),
+        Format::Cargo => Box::new(cargo::CargoReporter::new()),
+    };
+    Ok(reporter)
+}
+
+// A validation error with everything resolved against the source code.
+// The lines and columns are 1-based and the columns count characters.
+#[derive(Serialize)]
+pub struct Record<'a> {
+    #[serde(serialize_with = "to_string")]
+    pub code: RuleCode,
+    #[serde(serialize_with = "to_string")]
+    pub severity: Severity,
+    pub message: &'static str,
+    pub file: &'a str,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub snippet: &'a str,
+    pub fingerprint: String,
+    pub note: Option<&'a str>,
+    pub hint: Option<&'static str>,
+    pub fix: Option<FixRecord<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct FixRecord<'a> {
+    pub message: &'a str,
+    pub machine_applicable: bool,
+    pub edits: Vec<EditRecord<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct EditRecord<'a> {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub replacement: &'a str,
+}
+
+fn to_string<T: ToString, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
+
+// The paths are relative to the repository like `src/lib.rs` instead of `./src/lib.rs`
+// so that the CI annotations match the files of the diff.
+pub fn relative_path(path: &str) -> &str {
+    path.trim_start_matches("./")
+}
+
+pub fn records<'a>(file: &FileReport<'a>) -> Vec<Record<'a>> {
+    // The same finding on the same line text gets a different fingerprint by its occurrence.
+    let mut occurrences: HashMap<(RuleCode, &str), usize> = HashMap::new();
+    let path = relative_path(file.path);
+    let mut records = vec![];
+    for err in file.errs {
+        let start = &err.code_range.start;
+        let end = &err.code_range.end;
+        let line = line_text(file.source, start.byte).trim();
+        let occurrence = occurrences.entry((err.rule.code, line)).or_insert(0);
+        *occurrence += 1;
+        let key = format!(
+            "{}\0{}\0{}\0{}",
+            path,
+            err.rule.code.to_string(),
+            line,
+            occurrence
+        );
+        records.push(Record {
+            code: err.rule.code,
+            severity: err.rule.severity,
+            message: err.rule.desc,
+            file: path,
+            start_line: start.row + 1,
+            start_column: column(file.source, start.byte),
+            end_line: end.row + 1,
+            end_column: column(file.source, end.byte),
+            start_byte: start.byte,
+            end_byte: end.byte,
+            snippet: file.source.get(start.byte..end.byte).unwrap_or(""),
+            fingerprint: fingerprint(&key),
+            note: err.note.as_deref(),
+            hint: err.rule.hint.map(str::trim),
+            fix: err.fix.as_deref().map(fix_record),
+        });
+    }
+    records
+}
+
+fn fix_record(fix: &Fix) -> FixRecord<'_> {
+    FixRecord {
+        message: &fix.message,
+        machine_applicable: fix.applicability == Applicability::MachineApplicable,
+        edits: fix
+            .edits
+            .iter()
+            .map(|edit| EditRecord {
+                start_byte: edit.start,
+                end_byte: edit.end,
+                replacement: &edit.replacement,
+            })
+            .collect(),
+    }
+}
+
+// The line containing the byte offset without the line break.
+pub fn line_text(source: &str, byte: usize) -> &str {
+    let start = line_start(source, byte);
+    let rest = source.get(start..).unwrap_or("");
+    rest.lines().next().unwrap_or("")
+}
+
+fn line_start(source: &str, byte: usize) -> usize {
+    match source.get(..byte).and_then(|before| before.rfind('\n')) {
+        Some(newline) => newline + 1,
+        None => 0,
+    }
+}
+
+// The 1-based line number of the byte offset.
+pub fn line_number(source: &str, byte: usize) -> usize {
+    let before = source.get(..byte).unwrap_or(source);
+    before.matches('\n').count() + 1
+}
+
+// The 1-based column of the byte offset counted in characters.
+pub fn column(source: &str, byte: usize) -> usize {
+    let start = line_start(source, byte);
+    match source.get(start..byte) {
+        Some(before) => before.chars().count() + 1,
+        None => byte - start + 1,
+    }
+}
+
+pub fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 64-bit FNV-1a, which is stable across the Rust versions unlike `DefaultHasher`.
+pub fn fingerprint(key: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in key.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AllRulesValidator, SourceCode};
+
+    fn lint(source_code: &str) -> Vec<ValidationError> {
+        let source = assert_some!(SourceCode::parse(source_code));
+        let validator = AllRulesValidator::default();
+        validator.validate_all(&source.get_root_node(), source_code)
+    }
+
+    // Lint the source code as `src/lib.rs` and return the output of the reporter.
+    pub fn report(reporter: &mut dyn Reporter, source: &str) -> String {
+        report_at(reporter, "src/lib.rs", source)
+    }
+
+    pub fn report_at(reporter: &mut dyn Reporter, path: &str, source: &str) -> String {
+        let errs = lint(source);
+        let file = FileReport {
+            path,
+            source,
+            errs: &errs,
+        };
+        let mut out = vec![];
+        assert_ok!(reporter.report_file(&mut out, &file));
+        assert_ok!(reporter.finish(&mut out));
+        assert_ok!(String::from_utf8(out))
+    }
+
+    #[test]
+    fn test_records() {
+        let source = "fn f() {\n    let é = a.unwrap();\n    b.unwrap();\n    b.unwrap();\n}\n";
+        let errs = lint(source);
+        let file = FileReport {
+            path: "src/lib.rs",
+            source,
+            errs: &errs,
+        };
+        let original = records(&file);
+        assert_eq!(original.len(), 3);
+        let record = assert_some!(original.first());
+        assert_eq!(record.code, RuleCode::Unwrap);
+        assert_eq!((record.start_line, record.start_column), (2, 15));
+        assert_eq!((record.end_line, record.end_column), (2, 21));
+        assert_eq!(record.snippet, "unwrap");
+        assert_eq!(
+            source.get(record.start_byte..record.end_byte),
+            Some("unwrap")
+        );
+
+        // Only the line text and the occurrence matter.
+        let moved = format!("\n{}", source);
+        let errs = lint(&moved);
+        let file = FileReport {
+            path: "src/lib.rs",
+            source: &moved,
+            errs: &errs,
+        };
+        let moved_records = records(&file);
+        let fingerprints: Vec<&str> = original.iter().map(|r| r.fingerprint.as_str()).collect();
+        let moved_fingerprints: Vec<&str> = moved_records
+            .iter()
+            .map(|r| r.fingerprint.as_str())
+            .collect();
+        assert_eq!(fingerprints, moved_fingerprints);
+        assert_ne!(fingerprints.get(1), fingerprints.get(2));
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(
+            escape_xml(r#"a[i] < b && "c" != 'd'"#),
+            "a[i] &lt; b &amp;&amp; &quot;c&quot; != &apos;d&apos;"
+        );
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fingerprint(""), "cbf29ce484222325");
+        assert_eq!(fingerprint("a"), "af63dc4c8601ec8c");
+    }
+}
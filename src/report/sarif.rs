@@ -0,0 +1,151 @@
+// SARIF 2.1.0 log for the code scanning platforms.
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+use super::{records, FileReport, Record, Reporter};
+use crate::{Severity, RULES};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/doyoubi/mylint-rs";
+
+#[derive(Default)]
+pub struct SarifReporter {
+    results: Vec<Value>,
+}
+
+impl Reporter for SarifReporter {
+    fn report_file(&mut self, _out: &mut dyn Write, file: &FileReport) -> io::Result<()> {
+        for record in records(file) {
+            self.results.push(result(&record));
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        let rules: Vec<Value> = RULES
+            .iter()
+            .map(|rule| {
+                let mut descriptor = json!({
+                    "id": rule.code.to_string(),
+                    "shortDescription": { "text": rule.desc },
+                    "defaultConfiguration": { "level": level(rule.severity) },
+                });
+                if let Some(hint) = rule.hint {
+                    descriptor["help"] = json!({ "text": hint.trim() });
+                }
+                descriptor
+            })
+            .collect();
+        let log = json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "mylint",
+                        "informationUri": INFORMATION_URI,
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "columnKind": "unicodeCodePoints",
+                "results": self.results,
+            }]
+        });
+        serde_json::to_writer_pretty(&mut *out, &log)?;
+        writeln!(out)
+    }
+}
+
+fn result(record: &Record) -> Value {
+    let mut result = json!({
+        "ruleId": record.code.to_string(),
+        "level": level(record.severity),
+        "message": { "text": message(record) },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": record.file },
+                "region": {
+                    "startLine": record.start_line,
+                    "startColumn": record.start_column,
+                    "endLine": record.end_line,
+                    "endColumn": record.end_column,
+                    "byteOffset": record.start_byte,
+                    "byteLength": record.end_byte - record.start_byte,
+                    "snippet": { "text": record.snippet },
+                }
+            }
+        }],
+        "partialFingerprints": { "mylint/v1": record.fingerprint },
+    });
+    if let Some(index) = RULES.iter().position(|rule| rule.code == record.code) {
+        result["ruleIndex"] = json!(index);
+    }
+    if let Some(fix) = &record.fix {
+        let replacements: Vec<Value> = fix
+            .edits
+            .iter()
+            .map(|edit| {
+                json!({
+                    "deletedRegion": {
+                        "byteOffset": edit.start_byte,
+                        "byteLength": edit.end_byte - edit.start_byte,
+                    },
+                    "insertedContent": { "text": edit.replacement },
+                })
+            })
+            .collect();
+        result["fixes"] = json!([{
+            "description": { "text": fix.message },
+            "artifactChanges": [{
+                "artifactLocation": { "uri": record.file },
+                "replacements": replacements,
+            }],
+        }]);
+    }
+    result
+}
+
+fn message(record: &Record) -> String {
+    match record.note {
+        Some(note) => format!("{} ({})", record.message, note),
+        None => record.message.to_string(),
+    }
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::tests::report;
+
+    #[test]
+    fn test_sarif() {
+        let source = "fn f(a: Option<u8>) -> Option<u8> { Some(a.unwrap()) }";
+        let output = report(&mut SarifReporter::default(), source);
+        let log: Value = assert_ok!(serde_json::from_str(&output));
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][2]["id"], "Unwrap");
+        assert_eq!(
+            run["tool"]["driver"]["rules"][2]["shortDescription"]["text"],
+            "Unwrap call may panic."
+        );
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "Unwrap");
+        assert_eq!(result["ruleIndex"], 2);
+        let region = &result["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 1);
+        assert_eq!(region["startColumn"], 44);
+        let replacement = &result["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["deletedRegion"]["byteOffset"], 41);
+        assert_eq!(replacement["insertedContent"]["text"], "a?");
+        assert!(result["partialFingerprints"]["mylint/v1"].is_string());
+    }
+}
@@ -0,0 +1,179 @@
+// The totals printed at the end of a run.
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::relative_path;
+use crate::{RuleCode, Severity, ValidationError};
+
+pub struct Summary {
+    started: Instant,
+    rules: HashMap<RuleCode, usize>,
+    severities: HashMap<Severity, usize>,
+    files: HashMap<String, usize>,
+    dirs: HashMap<String, usize>,
+    pub scanned: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+impl Summary {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            rules: HashMap::new(),
+            severities: HashMap::new(),
+            files: HashMap::new(),
+            dirs: HashMap::new(),
+            scanned: 0,
+            skipped: 0,
+            failed: 0,
+        }
+    }
+
+    pub fn add_file(&mut self, path: &str, errs: &[ValidationError]) {
+        self.scanned += 1;
+        if errs.is_empty() {
+            return;
+        }
+        for err in errs {
+            *self.rules.entry(err.rule.code).or_insert(0) += 1;
+            *self.severities.entry(err.rule.severity).or_insert(0) += 1;
+        }
+        *self
+            .files
+            .entry(relative_path(path).to_string())
+            .or_insert(0) += errs.len();
+        *self.dirs.entry(directory(path)).or_insert(0) += errs.len();
+    }
+
+    pub fn findings(&self) -> usize {
+        self.rules.values().sum()
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.severities.get(&severity).copied().unwrap_or(0)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    // `top` limits the files and the directories to the ones with the most findings.
+    pub fn render(&self, out: &mut dyn Write, top: usize) -> io::Result<()> {
+        let rules: Vec<(String, usize)> = self
+            .rules
+            .iter()
+            .map(|(code, count)| (code.to_string(), *count))
+            .collect();
+        let rules = sorted(rules, usize::MAX);
+        let files = sorted(self.files.clone().into_iter().collect(), top);
+        let dirs = sorted(self.dirs.clone().into_iter().collect(), top);
+        let width = rules
+            .iter()
+            .chain(&files)
+            .chain(&dirs)
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("total".len());
+
+        writeln!(out, "Summary")?;
+        if !rules.is_empty() {
+            writeln!(out, "  Findings by rule:")?;
+            for (code, count) in &rules {
+                writeln!(out, "    {:<width$}  {:>6}", code, count, width = width)?;
+            }
+            writeln!(
+                out,
+                "    {:<width$}  {:>6}",
+                "total",
+                self.findings(),
+                width = width
+            )?;
+            writeln!(out, "  Top files:")?;
+            for (file, count) in &files {
+                writeln!(out, "    {:<width$}  {:>6}", file, count, width = width)?;
+            }
+            writeln!(out, "  Top directories:")?;
+            for (dir, count) in &dirs {
+                writeln!(out, "    {:<width$}  {:>6}", dir, count, width = width)?;
+            }
+        } else {
+            writeln!(out, "  No findings")?;
+        }
+        writeln!(
+            out,
+            "  Files: {} scanned, {} skipped, {} failed in {:.2}s",
+            self.scanned,
+            self.skipped,
+            self.failed,
+            self.elapsed().as_secs_f64()
+        )
+    }
+}
+
+// The directory of the file without the leading `./`.
+pub fn directory(path: &str) -> String {
+    Path::new(relative_path(path))
+        .parent()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .filter(|dir| !dir.is_empty())
+        .unwrap_or_else(|| ".".to_string())
+}
+
+// The most findings first and then by name so that the output is stable.
+fn sorted(mut counts: Vec<(String, usize)>, top: usize) -> Vec<(String, usize)> {
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(top);
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AllRulesValidator, SourceCode};
+
+    fn lint(source_code: &str) -> Vec<ValidationError> {
+        let source = assert_some!(SourceCode::parse(source_code));
+        AllRulesValidator::default().validate_all(&source.get_root_node(), source_code)
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut summary = Summary::new(Instant::now());
+        summary.add_file(
+            "src/a.rs",
+            &lint("fn f() { a.unwrap(); b.unwrap(); c[0]; }"),
+        );
+        summary.add_file("src/b/c.rs", &lint("fn f() { a.unwrap(); }"));
+        summary.add_file("src/b/d.rs", &lint("fn f() {}"));
+        summary.failed += 1;
+        assert_eq!(summary.findings(), 4);
+        assert_eq!(summary.count(Severity::Warning), 4);
+        assert_eq!(summary.count(Severity::Error), 0);
+
+        let mut out = vec![];
+        assert_ok!(summary.render(&mut out, 1));
+        let output = assert_ok!(String::from_utf8(out));
+        let expected = "Summary
+  Findings by rule:
+    Unwrap                3
+    IndexExpression       1
+    total                 4
+  Top files:
+    src/a.rs              3
+  Top directories:
+    src                   3
+  Files: 3 scanned, 0 skipped, 1 failed in ";
+        assert!(output.starts_with(expected), "{}", output);
+    }
+
+    #[test]
+    fn test_directory() {
+        assert_eq!(directory("./src/lib.rs"), "src");
+        assert_eq!(directory("src/lint/mod.rs"), "src/lint");
+        assert_eq!(directory("lib.rs"), ".");
+    }
+}
@@ -0,0 +1,557 @@
+// The output for the terminal in the style of rustc, with the source code highlighted when colored.
+use ansi_term::Colour::{Blue, Red, Yellow};
+use ansi_term::Style as Paint;
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::io::{self, Write};
+use std::str::FromStr;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Style, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+use super::{column, relative_path, FileReport, Reporter};
+use crate::{Applicability, Severity, ValidationError};
+
+// The spans longer than this only show their first and last lines.
+const MAX_SPAN_LINES: usize = 5;
+const COLLAPSED_LINES: usize = 2;
+const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            other => Err(format!("invalid color choice: {}", other)),
+        }
+    }
+}
+
+impl ColorChoice {
+    // `auto` only colors a terminal, and never when `NO_COLOR` is set as https://no-color.org asks.
+    pub fn use_color(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let no_color = matches!(env::var_os("NO_COLOR"), Some(v) if !v.is_empty());
+                !no_color && atty::is(atty::Stream::Stdout)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TextOptions {
+    pub color: bool,
+    // The name of a default theme or the path of a `.tmTheme` file.
+    pub theme: Option<String>,
+    // Don't highlight the source code and the hints.
+    pub plain: bool,
+    // The number of dimmed lines to show before and after each finding.
+    pub context: usize,
+}
+
+struct Palette {
+    warning: Paint,
+    error: Paint,
+    bold: Paint,
+    gutter: Paint,
+    dimmed: Paint,
+}
+
+impl Palette {
+    fn new(color: bool) -> Self {
+        if !color {
+            return Self {
+                warning: Paint::new(),
+                error: Paint::new(),
+                bold: Paint::new(),
+                gutter: Paint::new(),
+                dimmed: Paint::new(),
+            };
+        }
+        Self {
+            warning: Yellow.bold(),
+            error: Red.bold(),
+            bold: Paint::new().bold(),
+            gutter: Blue.bold(),
+            dimmed: Paint::new().dimmed(),
+        }
+    }
+}
+
+struct Highlighter {
+    syntax_set: SyntaxSet,
+    theme: Theme,
+}
+
+pub struct TextReporter {
+    palette: Palette,
+    highlighter: Option<Highlighter>,
+    context: usize,
+    // The highlighted hints by their text, shared by all the findings of the rule.
+    hints: HashMap<&'static str, Vec<String>>,
+}
+
+impl TextReporter {
+    pub fn new(options: &TextOptions) -> Result<Self, String> {
+        let theme = load_theme(options.theme.as_deref().unwrap_or(DEFAULT_THEME))?;
+        let highlighter = if options.color && !options.plain {
+            Some(Highlighter {
+                syntax_set: SyntaxSet::load_defaults_newlines(),
+                theme,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            palette: Palette::new(options.color),
+            highlighter,
+            context: options.context,
+            hints: HashMap::new(),
+        })
+    }
+
+    // Highlight the whole file at once since the state is carried over the lines.
+    fn highlight(&self, source: &str) -> io::Result<Vec<String>> {
+        let highlighter = match &self.highlighter {
+            Some(highlighter) => highlighter,
+            None => return Ok(source.lines().map(str::to_string).collect()),
+        };
+        let ps = &highlighter.syntax_set;
+        let syntax = ps
+            .find_syntax_by_extension("rs")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "failed to load rust syntax"))?;
+        let mut h = HighlightLines::new(syntax, &highlighter.theme);
+        Ok(LinesWithEndings::from(source)
+            .map(|line| {
+                let ranges: Vec<(Style, &str)> = h.highlight(line, ps);
+                let escaped = as_24_bit_terminal_escaped(ranges.as_slice(), false);
+                format!("{}\x1b[0m", escaped.trim_end_matches(&['\r', '\n'][..]))
+            })
+            .collect())
+    }
+
+    fn highlight_hint(&self, hint: &str) -> io::Result<Vec<String>> {
+        self.highlight(hint_text(hint))
+    }
+}
+
+fn hint_text(hint: &str) -> &str {
+    hint.trim_start_matches('\n').trim_end()
+}
+
+// A single finding as the text reporter prints it without the colors,
+// for the `rendered` diagnostic of the cargo format.
+pub fn render_plain(file: &FileReport, err: &ValidationError) -> io::Result<String> {
+    let lines: Vec<String> = file.source.lines().map(str::to_string).collect();
+    let hints: Vec<Vec<String>> = err
+        .rule
+        .hint
+        .iter()
+        .map(|hint| hint_text(hint).lines().map(str::to_string).collect())
+        .collect();
+    let hints: Vec<&[String]> = hints.iter().map(Vec::as_slice).collect();
+    let mut out = vec![];
+    for frame in frames(file.source, std::slice::from_ref(err), 0) {
+        render(
+            &mut out,
+            &Palette::new(false),
+            file,
+            &frame,
+            0,
+            &lines,
+            &hints,
+        )?;
+    }
+    String::from_utf8(out).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+// Check the theme even without the colors so that a typo is reported.
+fn load_theme(theme: &str) -> Result<Theme, String> {
+    if theme.ends_with(".tmTheme") {
+        return ThemeSet::get_theme(theme)
+            .map_err(|err| format!("failed to load theme {}: {}", theme, err));
+    }
+    let themes = ThemeSet::load_defaults().themes;
+    match themes.get(theme) {
+        Some(theme) => Ok(theme.clone()),
+        None => Err(format!(
+            "unknown theme {}, the available themes are: {}",
+            theme,
+            themes.keys().cloned().collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+impl Reporter for TextReporter {
+    fn report_file(&mut self, out: &mut dyn Write, file: &FileReport) -> io::Result<()> {
+        if file.errs.is_empty() {
+            return Ok(());
+        }
+        let highlighted = self.highlight(file.source)?;
+        for frame in frames(file.source, file.errs, self.context) {
+            let mut frame_hints = vec![];
+            for err in &frame.errs {
+                if let Some(hint) = err.rule.hint {
+                    if !frame_hints.contains(&hint) {
+                        frame_hints.push(hint);
+                    }
+                    if !self.hints.contains_key(hint) {
+                        let lines = self.highlight_hint(hint)?;
+                        self.hints.insert(hint, lines);
+                    }
+                }
+            }
+            let hints: Vec<&[String]> = frame_hints
+                .iter()
+                .filter_map(|hint| self.hints.get(hint))
+                .map(Vec::as_slice)
+                .collect();
+            render(
+                out,
+                &self.palette,
+                file,
+                &frame,
+                self.context,
+                &highlighted,
+                &hints,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// One line of the span with the 1-based character columns to underline.
+struct SpanLine<'a> {
+    number: usize,
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+fn span_lines<'a>(source: &'a str, err: &ValidationError) -> Vec<SpanLine<'a>> {
+    let start = &err.code_range.start;
+    let end = &err.code_range.end;
+    let start_column = column(source, start.byte);
+    let end_column = column(source, end.byte);
+    source
+        .lines()
+        .enumerate()
+        .skip(start.row)
+        .take(end.row.saturating_sub(start.row) + 1)
+        .map(|(i, text)| {
+            let indent = text.chars().take_while(|c| c.is_whitespace()).count() + 1;
+            let line_end = text.chars().count() + 1;
+            SpanLine {
+                number: i + 1,
+                text,
+                start: if i == start.row { start_column } else { indent },
+                end: if i == end.row { end_column } else { line_end },
+            }
+        })
+        .collect()
+}
+
+// The findings whose snippets overlap or touch share one frame.
+// The lines are 1-based and include the context lines.
+struct Frame<'e> {
+    errs: Vec<&'e ValidationError>,
+    first: usize,
+    last: usize,
+}
+
+fn frames<'e>(source: &str, errs: &'e [ValidationError], context: usize) -> Vec<Frame<'e>> {
+    let line_count = source.lines().count().max(1);
+    let mut sorted: Vec<&ValidationError> = errs.iter().collect();
+    sorted.sort_by_key(|err| (err.code_range.start.byte, err.code_range.end.byte));
+    let mut frames: Vec<Frame> = vec![];
+    for err in sorted {
+        let first = (err.code_range.start.row + 1)
+            .saturating_sub(context)
+            .max(1);
+        let last = (err.code_range.end.row + 1 + context).min(line_count);
+        match frames.last_mut() {
+            Some(frame) if first <= frame.last + 1 => {
+                frame.errs.push(err);
+                frame.last = frame.last.max(last);
+            }
+            _ => frames.push(Frame {
+                errs: vec![err],
+                first,
+                last,
+            }),
+        }
+    }
+    frames
+}
+
+// The lines to show: the context lines and the spans without the middle of the long ones.
+fn visible_lines(source: &str, frame: &Frame, context: usize) -> BTreeSet<usize> {
+    let mut visible = BTreeSet::new();
+    for err in &frame.errs {
+        let lines = span_lines(source, err);
+        let collapsed = lines.len() > MAX_SPAN_LINES;
+        for (i, line) in lines.iter().enumerate() {
+            if !collapsed || i < COLLAPSED_LINES || i >= lines.len() - COLLAPSED_LINES {
+                visible.insert(line.number);
+            }
+        }
+        let start = err.code_range.start.row + 1;
+        let end = err.code_range.end.row + 1;
+        visible.extend(start.saturating_sub(context).max(frame.first)..start);
+        visible.extend(end + 1..=(end + context).min(frame.last));
+    }
+    visible
+}
+
+// The whitespace before the underline keeps the tabs of the line so that they line up.
+fn underline(line: &SpanLine) -> (String, String) {
+    let padding = line
+        .text
+        .chars()
+        .take(line.start.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let width = line.end.saturating_sub(line.start).max(1);
+    (padding, "^".repeat(width))
+}
+
+fn render(
+    out: &mut dyn Write,
+    palette: &Palette,
+    file: &FileReport,
+    frame: &Frame,
+    context: usize,
+    highlighted: &[String],
+    hints: &[&[String]],
+) -> io::Result<()> {
+    let colour = |err: &ValidationError| match err.rule.severity {
+        Severity::Warning => palette.warning,
+        Severity::Error => palette.error,
+    };
+    let gutter = palette.gutter;
+    let visible = visible_lines(file.source, frame, context);
+    let last_number = visible.iter().next_back().copied().unwrap_or(1);
+    let width = last_number.to_string().len();
+    let blank = " ".repeat(width);
+    // Label the underlines with the rule codes when they can't be told apart.
+    let labeled = frame.errs.len() > 1;
+
+    for err in &frame.errs {
+        writeln!(
+            out,
+            "{}{}",
+            colour(err).paint(format!(
+                "{}[{}]",
+                err.rule.severity,
+                err.rule.code.to_string()
+            )),
+            palette.bold.paint(format!(": {}", err.rule.desc))
+        )?;
+        writeln!(
+            out,
+            "{}{} {}:{}:{}",
+            blank,
+            gutter.paint("-->"),
+            relative_path(file.path),
+            err.code_range.start.row + 1,
+            column(file.source, err.code_range.start.byte)
+        )?;
+    }
+    writeln!(out, "{} {}", blank, gutter.paint("|"))?;
+
+    let spans: Vec<Vec<SpanLine>> = frame
+        .errs
+        .iter()
+        .map(|err| span_lines(file.source, err))
+        .collect();
+    let mut previous: Option<usize> = None;
+    for &number in &visible {
+        if matches!(previous, Some(p) if number > p + 1) {
+            writeln!(out, "{}", gutter.paint("..."))?;
+        }
+        previous = Some(number);
+
+        let in_span = spans
+            .iter()
+            .any(|lines| lines.iter().any(|line| line.number == number));
+        let code = if in_span {
+            highlighted.get(number - 1).cloned().unwrap_or_default()
+        } else {
+            let text = file.source.lines().nth(number - 1).unwrap_or("");
+            palette.dimmed.paint(text).to_string()
+        };
+        writeln!(
+            out,
+            "{} {}",
+            gutter.paint(format!("{:>width$} |", number, width = width)),
+            code
+        )?;
+
+        for (err, lines) in frame.errs.iter().zip(&spans) {
+            let line = match lines.iter().find(|line| line.number == number) {
+                Some(line) => line,
+                None => continue,
+            };
+            let (padding, carets) = underline(line);
+            let label = match lines.last() {
+                Some(last) if labeled && last.number == number => {
+                    format!(" {}", err.rule.code.to_string())
+                }
+                _ => String::new(),
+            };
+            writeln!(
+                out,
+                "{} {} {}{}",
+                blank,
+                gutter.paint("|"),
+                padding,
+                colour(err).paint(format!("{}{}", carets, label))
+            )?;
+        }
+    }
+
+    for err in &frame.errs {
+        let prefix = if labeled {
+            format!("[{}] ", err.rule.code.to_string())
+        } else {
+            String::new()
+        };
+        if let Some(note) = &err.note {
+            writeln!(
+                out,
+                "{} {} {}{}{}",
+                blank,
+                gutter.paint("="),
+                palette.bold.paint("note: "),
+                prefix,
+                note
+            )?;
+        }
+        if let Some(fix) = &err.fix {
+            let applicability = match fix.applicability {
+                Applicability::MachineApplicable => " (run with --fix to apply)",
+                Applicability::Suggestion => "",
+            };
+            writeln!(
+                out,
+                "{} {} {}{}{}{}",
+                blank,
+                gutter.paint("="),
+                palette.bold.paint("help: "),
+                prefix,
+                fix.message,
+                applicability
+            )?;
+        }
+    }
+    for hint in hints {
+        writeln!(out, "{}", palette.bold.paint("help:"))?;
+        for line in hint.iter() {
+            writeln!(out, "{}    {}", blank, line)?;
+        }
+    }
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::tests::report;
+
+    fn render_text(source: &str) -> String {
+        let mut reporter = assert_ok!(TextReporter::new(&TextOptions::default()));
+        report(&mut reporter, source)
+    }
+
+    #[test]
+    fn test_color() {
+        let options = TextOptions {
+            color: true,
+            ..TextOptions::default()
+        };
+        let mut reporter = assert_ok!(TextReporter::new(&options));
+        let output = report(&mut reporter, "fn f() { a.unwrap(); }");
+        assert!(output.contains("\x1b["));
+        // The hint is highlighted once for all the findings.
+        assert_eq!(report(&mut reporter, "fn f() { a.unwrap(); }"), output);
+        assert_eq!(reporter.hints.len(), 1);
+        assert!(!render_text("fn f() { a.unwrap(); }").contains('\x1b'));
+        assert_err!(load_theme("no-such-theme"));
+    }
+
+    #[test]
+    fn test_render() {
+        let source = "fn f(a: Option<u8>) -> Option<u8> {\n    Some(a.unwrap())\n}\n";
+        let expected = "warning[Unwrap]: Unwrap call may panic.
+ --> src/lib.rs:2:12
+  |
+2 |     Some(a.unwrap())
+  |            ^^^^^^
+  = help: replace it with `a?` (run with --fix to apply)
+
+";
+        assert_eq!(render_text(source), expected);
+    }
+
+    #[test]
+    fn test_collapsed_span() {
+        let source =
+            "fn f() {\n    a[\n        b(\n            1,\n            2,\n        )\n    ];\n}\n";
+        let output = render_text(source);
+        let expected =
+            "warning[IndexExpression]: index operation may panic, use get method instead.
+ --> src/lib.rs:2:5
+  |
+2 |     a[
+  |     ^^
+3 |         b(
+  |         ^^
+...
+6 |         )
+  |         ^
+7 |     ];
+  |     ^
+";
+        assert!(output.starts_with(expected), "{}", output);
+    }
+
+    #[test]
+    fn test_context() {
+        let options = TextOptions {
+            context: 1,
+            ..TextOptions::default()
+        };
+        let mut reporter = assert_ok!(TextReporter::new(&options));
+        let source = "fn f() {\n    let a = b.unwrap();\n    let c = d[0];\n}\n\n\nfn g() {\n    e.unwrap();\n}\n";
+        let output = report(&mut reporter, source);
+        let expected = "warning[Unwrap]: Unwrap call may panic.
+ --> src/lib.rs:2:15
+warning[IndexExpression]: index operation may panic, use get method instead.
+ --> src/lib.rs:3:13
+  |
+1 | fn f() {
+2 |     let a = b.unwrap();
+  |               ^^^^^^ Unwrap
+3 |     let c = d[0];
+  |             ^^^^ IndexExpression
+4 | }
+  = help: [IndexExpression] replace it with `d.get(0)`
+";
+        assert!(output.starts_with(expected), "{}", output);
+        assert!(output.contains("\n  |\n7 | fn g() {\n8 |     e.unwrap();\n"));
+    }
+}